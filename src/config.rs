@@ -5,10 +5,22 @@ use std::{
 
 use configparser::ini::Ini;
 
+use crate::{
+	destination,
+	download_options::{MetadataOptions, SPONSORBLOCK_CATEGORIES, SponsorBlockOptions, THUMBNAIL_FORMATS},
+	output_template::TemplateKind,
+	presets::FormatPreset,
+	retry::{ErrorClass, RetryPolicy},
+};
+
 const CONFIG_DIRECTORY: &str = "Tubex";
 const CONFIG_FILENAME: &str = "Tubex.ini";
 const CUSTOM_COMMANDS_SECTION: &str = "CustomCommands";
+const FORMAT_PRESETS_SECTION: &str = "FormatPresets";
+const RECENT_DESTINATIONS_SECTION: &str = "RecentDestinations";
 const SETTINGS_SECTION: &str = "Settings";
+const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 3;
+const ARCHIVE_FILENAME: &str = "Tubex.archive.txt";
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct CustomCommand {
@@ -31,6 +43,8 @@ impl ConfigManager {
 		Self { data, config_path: path }
 	}
 
+	pub fn data_file(&self, name: &str) -> PathBuf { self.config_path.with_file_name(name) }
+
 	pub fn flush(&self) {
 		self.config_path.parent().map(fs::create_dir_all);
 		let _ = self.data.write(&self.config_path);
@@ -56,6 +70,39 @@ impl ConfigManager {
 		}
 	}
 
+	pub fn get_presets(&self) -> Vec<FormatPreset> {
+		let map = self.data.get_map_ref();
+		let Some(section) = map.get("formatpresets").or_else(|| map.get(FORMAT_PRESETS_SECTION)) else {
+			return FormatPreset::defaults();
+		};
+		let mut entries: Vec<(usize, FormatPreset)> = section
+			.iter()
+			.filter_map(|(k, v)| {
+				let index = k.trim_start_matches("preset").parse().ok()?;
+				Some((index, FormatPreset::from_config_value(v.as_deref()?)?))
+			})
+			.collect();
+		entries.sort_by_key(|(i, _)| *i);
+		entries.into_iter().map(|(_, p)| p).collect()
+	}
+
+	pub fn set_presets(&mut self, presets: &[FormatPreset]) {
+		self.data.remove_section(FORMAT_PRESETS_SECTION);
+		self.data.set(FORMAT_PRESETS_SECTION, "count", Some(presets.len().to_string()));
+		for (i, preset) in presets.iter().enumerate() {
+			self.data.set(FORMAT_PRESETS_SECTION, &format!("preset{}", i + 1), Some(preset.to_config_value()));
+		}
+	}
+
+	pub fn get_default_preset(&self) -> Option<FormatPreset> {
+		let name = self.data.get(SETTINGS_SECTION, "default_preset")?;
+		self.get_presets().into_iter().find(|p| p.name == name.trim())
+	}
+
+	pub fn set_default_preset(&mut self, name: Option<&str>) {
+		self.data.set(SETTINGS_SECTION, "default_preset", Some(name.unwrap_or_default().to_string()));
+	}
+
 	pub fn get_download_path(&self) -> Option<String> { self.data.get(SETTINGS_SECTION, "download_path") }
 
 	pub fn set_download_path(&mut self, path: &str) {
@@ -85,12 +132,211 @@ impl ConfigManager {
 	}
 
 	pub fn get_update_channel(&self) -> String {
-		self.data.get(SETTINGS_SECTION, "update_channel").unwrap_or_else(|| "stable".to_string())
+		self.data
+			.get(SETTINGS_SECTION, "update_channel")
+			.map(|c| c.trim().to_string())
+			.filter(|c| !c.is_empty())
+			.unwrap_or_else(|| "stable".to_string())
 	}
 
 	pub fn set_update_channel(&mut self, channel: &str) {
 		self.data.set(SETTINGS_SECTION, "update_channel", Some(channel.to_string()));
 	}
+
+	pub fn get_auto_update(&self) -> bool {
+		self.data.get(SETTINGS_SECTION, "auto_update").is_some_and(|v| v.trim() == "true")
+	}
+
+	pub fn set_auto_update(&mut self, enabled: bool) {
+		self.data.set(SETTINGS_SECTION, "auto_update", Some(enabled.to_string()));
+	}
+
+	pub fn get_pinned_version(&self) -> Option<String> {
+		self.data.get(SETTINGS_SECTION, "pinned_version").filter(|v| !v.trim().is_empty())
+	}
+
+	pub fn set_pinned_version(&mut self, version: &str) {
+		self.data.set(SETTINGS_SECTION, "pinned_version", Some(version.trim().to_string()));
+	}
+
+	pub fn get_tool_mirrors(&self) -> Vec<String> {
+		self.data
+			.get(SETTINGS_SECTION, "tool_mirrors")
+			.map(|m| m.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+			.unwrap_or_default()
+	}
+
+	pub fn set_tool_mirrors(&mut self, mirrors: &str) {
+		self.data.set(SETTINGS_SECTION, "tool_mirrors", Some(mirrors.to_string()));
+	}
+
+	pub fn get_subtitle_languages(&self) -> Vec<String> {
+		self.data
+			.get(SETTINGS_SECTION, "subtitle_languages")
+			.map(|m| m.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+			.unwrap_or_default()
+	}
+
+	pub fn set_subtitle_languages(&mut self, languages: &[String]) {
+		self.data.set(SETTINGS_SECTION, "subtitle_languages", Some(languages.join(",")));
+	}
+
+	pub fn get_sponsorblock(&self) -> SponsorBlockOptions {
+		let categories = |key: &str| -> Vec<String> {
+			self.data
+				.get(SETTINGS_SECTION, key)
+				.map(|v| {
+					v.split(',')
+						.map(|c| c.trim().to_string())
+						.filter(|c| SPONSORBLOCK_CATEGORIES.contains(&c.as_str()))
+						.collect()
+				})
+				.unwrap_or_default()
+		};
+		SponsorBlockOptions { mark: categories("sponsorblock_mark"), remove: categories("sponsorblock_remove") }
+	}
+
+	pub fn set_sponsorblock(&mut self, sponsorblock: &SponsorBlockOptions) {
+		self.data.set(SETTINGS_SECTION, "sponsorblock_mark", Some(sponsorblock.mark.join(",")));
+		self.data.set(SETTINGS_SECTION, "sponsorblock_remove", Some(sponsorblock.remove.join(",")));
+	}
+
+	pub fn get_sponsorblock_api(&self) -> String {
+		self.data.get(SETTINGS_SECTION, "sponsorblock_api").unwrap_or_default()
+	}
+
+	pub fn set_sponsorblock_api(&mut self, url: &str) {
+		self.data.set(SETTINGS_SECTION, "sponsorblock_api", Some(url.trim().to_string()));
+	}
+
+	pub fn get_metadata_options(&self) -> MetadataOptions {
+		let flag = |key: &str| self.data.get(SETTINGS_SECTION, key).is_some_and(|v| v.trim() == "true");
+		MetadataOptions {
+			embed_thumbnail: flag("embed_thumbnail"),
+			thumbnail_format: self
+				.data
+				.get(SETTINGS_SECTION, "thumbnail_format")
+				.filter(|f| THUMBNAIL_FORMATS[1..].contains(&f.trim())),
+			square_thumbnail: flag("square_thumbnail"),
+			embed_metadata: flag("embed_metadata"),
+			write_info_json: flag("write_info_json"),
+			write_description: flag("write_description"),
+		}
+	}
+
+	pub fn set_metadata_options(&mut self, metadata: &MetadataOptions) {
+		for (key, value) in [
+			("embed_thumbnail", metadata.embed_thumbnail),
+			("square_thumbnail", metadata.square_thumbnail),
+			("embed_metadata", metadata.embed_metadata),
+			("write_info_json", metadata.write_info_json),
+			("write_description", metadata.write_description),
+		] {
+			self.data.set(SETTINGS_SECTION, key, Some(value.to_string()));
+		}
+		self.data.set(SETTINGS_SECTION, "thumbnail_format", metadata.thumbnail_format.clone());
+	}
+
+	pub fn get_output_template(&self, kind: TemplateKind) -> String {
+		self.data.get(SETTINGS_SECTION, kind.config_key()).unwrap_or_default()
+	}
+
+	pub fn set_output_template(&mut self, kind: TemplateKind, template: &str) {
+		self.data.set(SETTINGS_SECTION, kind.config_key(), Some(template.trim().to_string()));
+	}
+
+	pub fn get_recent_destinations(&self) -> Vec<String> {
+		(1..=destination::MAX_RECENT)
+			.filter_map(|i| self.data.get(RECENT_DESTINATIONS_SECTION, &format!("folder{}", i)))
+			.map(|f| f.trim().to_string())
+			.filter(|f| !f.is_empty())
+			.collect()
+	}
+
+	pub fn add_recent_destination(&mut self, folder: &str) {
+		let mut recent = self.get_recent_destinations();
+		destination::remember(&mut recent, folder);
+		self.data.remove_section(RECENT_DESTINATIONS_SECTION);
+		for (i, folder) in recent.iter().enumerate() {
+			self.data.set(RECENT_DESTINATIONS_SECTION, &format!("folder{}", i + 1), Some(folder.clone()));
+		}
+	}
+
+	pub fn get_playlist_subfolder(&self) -> bool {
+		self.data.get(SETTINGS_SECTION, "playlist_subfolder").is_some_and(|v| v.trim() == "true")
+	}
+
+	pub fn set_playlist_subfolder(&mut self, enabled: bool) {
+		self.data.set(SETTINGS_SECTION, "playlist_subfolder", Some(enabled.to_string()));
+	}
+
+	pub fn get_max_concurrent_downloads(&self) -> usize {
+		self.data
+			.get(SETTINGS_SECTION, "max_concurrent_downloads")
+			.and_then(|v| v.trim().parse().ok())
+			.filter(|n| *n > 0)
+			.unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS)
+	}
+
+	pub fn set_max_concurrent_downloads(&mut self, max: usize) {
+		self.data.set(SETTINGS_SECTION, "max_concurrent_downloads", Some(max.to_string()));
+	}
+
+	pub fn get_keep_partial_files(&self) -> bool {
+		self.data.get(SETTINGS_SECTION, "keep_partial_files").is_none_or(|v| v.trim() != "false")
+	}
+
+	pub fn set_keep_partial_files(&mut self, keep: bool) {
+		self.data.set(SETTINGS_SECTION, "keep_partial_files", Some(keep.to_string()));
+	}
+
+	pub fn get_check_format_selector(&self) -> bool {
+		self.data.get(SETTINGS_SECTION, "check_format_selector").is_some_and(|v| v.trim() == "true")
+	}
+
+	pub fn set_check_format_selector(&mut self, check: bool) {
+		self.data.set(SETTINGS_SECTION, "check_format_selector", Some(check.to_string()));
+	}
+
+	pub fn get_archive_mode(&self) -> String {
+		self.data.get(SETTINGS_SECTION, "download_archive").unwrap_or_else(|| "off".to_string())
+	}
+
+	pub fn set_archive_mode(&mut self, mode: &str) {
+		self.data.set(SETTINGS_SECTION, "download_archive", Some(mode.to_string()));
+	}
+
+	pub fn archive_path(&self, download_dir: Option<&str>) -> Option<PathBuf> {
+		match self.get_archive_mode().as_str() {
+			"global" => Some(self.data_file(ARCHIVE_FILENAME)),
+			"per_folder" => Some(match download_dir.filter(|d| !d.is_empty()) {
+				Some(dir) => Path::new(dir).join(ARCHIVE_FILENAME),
+				None => self.data_file(ARCHIVE_FILENAME),
+			}),
+			_ => None,
+		}
+	}
+
+	pub fn get_retry_policy(&self) -> RetryPolicy {
+		let default = RetryPolicy::default();
+		let number = |key: &str| self.data.get(SETTINGS_SECTION, key).and_then(|v| v.trim().parse::<u64>().ok());
+		RetryPolicy {
+			max_attempts: number("retry_max_attempts").map_or(default.max_attempts, |n| n.max(1) as u32),
+			base_delay_secs: number("retry_base_delay").unwrap_or(default.base_delay_secs),
+			retry_on: self
+				.data
+				.get(SETTINGS_SECTION, "retry_on")
+				.map(|v| v.split(',').filter_map(ErrorClass::from_key).collect())
+				.unwrap_or(default.retry_on),
+		}
+	}
+
+	pub fn set_retry_policy(&mut self, policy: &RetryPolicy) {
+		self.data.set(SETTINGS_SECTION, "retry_max_attempts", Some(policy.max_attempts.to_string()));
+		self.data.set(SETTINGS_SECTION, "retry_base_delay", Some(policy.base_delay_secs.to_string()));
+		let retry_on = policy.retry_on.iter().map(|c| c.key()).collect::<Vec<_>>().join(",");
+		self.data.set(SETTINGS_SECTION, "retry_on", Some(retry_on));
+	}
 }
 
 fn get_config_path() -> PathBuf {
@@ -1,8 +1,22 @@
-use std::sync::{Arc, Mutex};
+use std::{
+	cell::RefCell,
+	io::{BufRead, BufReader},
+	process::Stdio,
+	rc::Rc,
+	sync::{Arc, Mutex, mpsc},
+	thread,
+};
 
 use wxdragon::prelude::*;
 
-use crate::config::{ConfigManager, CustomCommand};
+use crate::{
+	config::{ConfigManager, CustomCommand},
+	options_dialog::AudioControls,
+	output_template::{self, TemplateKind},
+	platform,
+	presets::FormatPreset,
+	video_info::VideoInfo,
+};
 
 const RET_OK: i32 = 1;
 const RET_CANCEL: i32 = 0;
@@ -183,3 +197,332 @@ pub fn show_config_dialog(parent: &impl WxWidget, config_manager: Arc<Mutex<Conf
 	dialog.centre();
 	let _ = dialog.show_modal();
 }
+
+pub fn show_preset_dialog(parent: &impl WxWidget, title: &str, preset: Option<&FormatPreset>) -> Option<FormatPreset> {
+	let dialog = Dialog::builder(parent, title).with_size(500, 300).build();
+	let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
+
+	let mut fields = Vec::new();
+	for label in ["Name:", "Format (-f):", "Sort (-S):"] {
+		let row = BoxSizer::builder(Orientation::Horizontal).build();
+		row.add(
+			&StaticText::builder(&dialog).with_label(label).build(),
+			0,
+			SizerFlag::AlignCenterVertical | SizerFlag::All,
+			5,
+		);
+		let text = TextCtrl::builder(&dialog).build();
+		row.add(&text, 1, SizerFlag::Expand | SizerFlag::All, 5);
+		main_sizer.add_sizer(&row, 0, SizerFlag::Expand | SizerFlag::All, 5);
+		fields.push(text);
+	}
+	let (name_text, format_text, sort_text) = (fields[0], fields[1], fields[2]);
+	main_sizer.add(
+		&StaticText::builder(&dialog).with_label("Example sort: res:1080,vcodec:h264,acodec:aac").build(),
+		0,
+		SizerFlag::All,
+		5,
+	);
+	let audio = AudioControls::create(&dialog, &main_sizer);
+
+	if let Some(p) = preset {
+		name_text.set_value(&p.name);
+		format_text.set_value(&p.format);
+		sort_text.set_value(&p.sort);
+		audio.set(p.extract_audio.as_ref());
+	}
+
+	let btn_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let ok_btn = Button::builder(&dialog).with_label("OK").build();
+	let cancel_btn = Button::builder(&dialog).with_label("Cancel").build();
+	btn_sizer.add_stretch_spacer(1);
+	btn_sizer.add(&ok_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add(&cancel_btn, 0, SizerFlag::All, 5);
+	main_sizer.add_stretch_spacer(1);
+	main_sizer.add_sizer(&btn_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	dialog.set_sizer(main_sizer, true);
+	dialog.centre();
+
+	let d_ok = dialog;
+	ok_btn.on_click(move |_| d_ok.end_modal(RET_OK));
+	let d_cancel = dialog;
+	cancel_btn.on_click(move |_| d_cancel.end_modal(RET_CANCEL));
+
+	if dialog.show_modal() == RET_OK {
+		let preset = FormatPreset {
+			extract_audio: audio.extraction(),
+			..FormatPreset::new(&name_text.get_value(), &format_text.get_value(), &sort_text.get_value())
+		};
+		if preset.name.is_empty() || preset.name.contains('|') {
+			show_custom_message(&dialog, "The name cannot be empty or contain '|'.", "Error");
+			None
+		} else if preset.format.contains('|') || preset.sort.contains('|') {
+			show_custom_message(&dialog, "The format and sort rules cannot contain '|'.", "Error");
+			None
+		} else if preset.format.is_empty() && preset.sort.is_empty() {
+			show_custom_message(&dialog, "Enter a format, sort rules, or both.", "Error");
+			None
+		} else {
+			Some(preset)
+		}
+	} else {
+		None
+	}
+}
+
+pub fn show_presets_dialog(parent: &impl WxWidget, config_manager: Arc<Mutex<ConfigManager>>) {
+	let dialog = Dialog::builder(parent, "Configure Format Presets").with_size(600, 400).build();
+	let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
+	let content_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+
+	let presets: Vec<String> = config_manager
+		.lock()
+		.expect("Config manager lock failed")
+		.get_presets()
+		.iter()
+		.map(|p| p.name.clone())
+		.collect();
+	let list_box = ListBox::builder(&dialog).with_choices(presets).build();
+	content_sizer.add(&list_box, 1, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let btn_sizer = BoxSizer::builder(Orientation::Vertical).build();
+	let add_btn = Button::builder(&dialog).with_label("Add...").build();
+	let edit_btn = Button::builder(&dialog).with_label("Edit...").build();
+	let remove_btn = Button::builder(&dialog).with_label("Remove").build();
+	btn_sizer.add(&add_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add(&edit_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add(&remove_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add_stretch_spacer(1);
+	content_sizer.add_sizer(&btn_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+	main_sizer.add_sizer(&content_sizer, 1, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let std_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let ok_btn = Button::builder(&dialog).with_label("OK").build();
+	let cancel_btn = Button::builder(&dialog).with_label("Cancel").build();
+	std_sizer.add_stretch_spacer(1);
+	std_sizer.add(&ok_btn, 0, SizerFlag::All, 5);
+	std_sizer.add(&cancel_btn, 0, SizerFlag::All, 5);
+	main_sizer.add_sizer(&std_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let dlg = dialog;
+	let cfg = Arc::clone(&config_manager);
+	let lb_clone = list_box;
+	add_btn.on_click(move |_| {
+		if let Some(new_preset) = show_preset_dialog(&dlg, "Add Preset", None) {
+			let mut cfg = cfg.lock().expect("Config manager lock failed");
+			let mut presets = cfg.get_presets();
+			presets.push(new_preset.clone());
+			cfg.set_presets(&presets);
+			lb_clone.append(&new_preset.name);
+		}
+	});
+
+	let dlg2 = dialog;
+	let cfg2 = Arc::clone(&config_manager);
+	let lb2 = list_box;
+	edit_btn.on_click(move |_| {
+		if let Some(sel) = lb2.get_selection() {
+			let preset_opt = cfg2.lock().expect("Config manager lock failed").get_presets().get(sel as usize).cloned();
+			if let Some(preset) = preset_opt
+				&& let Some(edited) = show_preset_dialog(&dlg2, "Edit Preset", Some(&preset))
+			{
+				let mut cfg = cfg2.lock().expect("Config manager lock failed");
+				let was_default = cfg.get_default_preset().is_some_and(|d| d.name == preset.name);
+				let mut presets = cfg.get_presets();
+				presets[sel as usize] = edited.clone();
+				cfg.set_presets(&presets);
+				if was_default {
+					cfg.set_default_preset(Some(&edited.name));
+				}
+				lb2.clear();
+				for p in &presets {
+					lb2.append(&p.name);
+				}
+				lb2.set_selection(sel, true);
+			}
+		}
+	});
+
+	let dlg3 = dialog;
+	let cfg3 = Arc::clone(&config_manager);
+	let lb3 = list_box;
+	remove_btn.on_click(move |_| {
+		if let Some(sel) = lb3.get_selection()
+			&& show_custom_message(&dlg3, "Are you sure you want to remove this preset?", "Confirm") == RET_OK
+		{
+			let mut cfg = cfg3.lock().expect("Config manager lock failed");
+			let mut presets = cfg.get_presets();
+			if (sel as usize) < presets.len() {
+				presets.remove(sel as usize);
+				cfg.set_presets(&presets);
+				lb3.delete(sel);
+			}
+		}
+	});
+
+	let dlg4 = dialog;
+	let cfg4 = Arc::clone(&config_manager);
+	ok_btn.on_click(move |_| {
+		cfg4.lock().expect("Config manager lock failed").flush();
+		dlg4.end_modal(RET_OK);
+	});
+	let dlg5 = dialog;
+	cancel_btn.on_click(move |_| dlg5.end_modal(RET_CANCEL));
+
+	dialog.set_sizer(main_sizer, true);
+	dialog.centre();
+	let _ = dialog.show_modal();
+}
+
+fn load_sample_info(yt_dlp_path: &str, global_flags: &str, url: &str) -> Result<VideoInfo, String> {
+	let mut cmd = platform::command(yt_dlp_path);
+	cmd.env("PYTHONIOENCODING", "utf-8");
+	cmd.arg("--encoding").arg("utf-8");
+	let args_iter = shell_words::split(global_flags)
+		.unwrap_or_else(|_| global_flags.split_whitespace().map(String::from).collect());
+	for arg in args_iter {
+		cmd.arg(platform::expand_env_vars(&arg));
+	}
+	cmd.arg("--dump-json").arg("--no-warnings").arg("--playlist-items").arg("1").arg(url);
+	cmd.stdout(Stdio::piped()).stderr(Stdio::null());
+	let mut child = cmd.spawn().map_err(|e| format!("Spawn failed: {}", e))?;
+	let line = child
+		.stdout
+		.take()
+		.and_then(|out| BufReader::new(out).lines().map_while(Result::ok).find(|l| l.starts_with('{')));
+	let _ = child.wait();
+	let line = line.ok_or_else(|| "yt-dlp returned no video information".to_string())?;
+	serde_json::from_str(&line).map_err(|e| format!("Could not parse video information: {}", e))
+}
+
+pub fn show_output_templates_dialog(parent: &impl WxWidget, config_manager: Arc<Mutex<ConfigManager>>) {
+	let dialog = Dialog::builder(parent, "Output Filename Templates").with_size(700, 480).build();
+	let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
+
+	let sample_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	sample_sizer.add(
+		&StaticText::builder(&dialog).with_label("Preview URL:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let url_text = TextCtrl::builder(&dialog).build();
+	sample_sizer.add(&url_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	let load_btn = Button::builder(&dialog).with_label("Load").build();
+	sample_sizer.add(&load_btn, 0, SizerFlag::All, 5);
+	main_sizer.add_sizer(&sample_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+	let sample_status = StaticText::builder(&dialog).with_label("Previewing with built-in sample data.").build();
+	main_sizer.add(&sample_status, 0, SizerFlag::All, 5);
+
+	let editors = {
+		let cfg = config_manager.lock().expect("Config manager lock failed");
+		TemplateKind::ALL.map(|kind| {
+			main_sizer.add(&StaticText::builder(&dialog).with_label(kind.label()).build(), 0, SizerFlag::All, 5);
+			let text = TextCtrl::builder(&dialog).build();
+			text.set_value(&cfg.get_output_template(kind));
+			main_sizer.add(&text, 0, SizerFlag::Expand | SizerFlag::Left | SizerFlag::Right, 5);
+			let preview = StaticText::builder(&dialog).build();
+			main_sizer.add(&preview, 0, SizerFlag::All, 5);
+			(kind, text, preview)
+		})
+	};
+	main_sizer.add(
+		&StaticText::builder(&dialog)
+			.with_label(&format!(
+				"Leave a template empty to use yt-dlp's default ({}).\nExamples: %(uploader)s, %(upload_date>%Y-%m-%d)s, %(playlist_title)s/%(playlist_index)03d - %(title)s.%(ext)s",
+				output_template::DEFAULT_TEMPLATE
+			))
+			.build(),
+		0,
+		SizerFlag::All,
+		5,
+	);
+
+	let sample = Rc::new(RefCell::new(output_template::sample_info()));
+	let refresh = {
+		let sample = Rc::clone(&sample);
+		Rc::new(move || {
+			let info = sample.borrow();
+			for (kind, text, preview) in &editors {
+				let template = text.get_value();
+				let template =
+					if template.trim().is_empty() { output_template::DEFAULT_TEMPLATE } else { template.trim() };
+				let rendered = match kind {
+					TemplateKind::Audio => {
+						output_template::render(template, &VideoInfo { ext: Some("mp3".to_string()), ..info.clone() })
+					}
+					_ => output_template::render(template, &info),
+				};
+				preview.set_label(&format!("Preview: {}", rendered));
+			}
+		})
+	};
+	refresh();
+	for (_, text, _) in &editors {
+		let refresh_text = Rc::clone(&refresh);
+		text.on_text_changed(move |_| refresh_text());
+	}
+
+	let (tx, rx) = mpsc::channel::<Result<VideoInfo, String>>();
+	let cfg_load = Arc::clone(&config_manager);
+	load_btn.on_click(move |_| {
+		let url = url_text.get_value().trim().to_string();
+		if url.is_empty() {
+			return;
+		}
+		let (yt_dlp_path, global_flags) = {
+			let cfg = cfg_load.lock().expect("Config manager lock failed");
+			(cfg.get_yt_dlp_path(), cfg.get_global_flags())
+		};
+		load_btn.enable(false);
+		sample_status.set_label(&format!("Loading {}...", url));
+		let tx = tx.clone();
+		thread::spawn(move || {
+			let _ = tx.send(load_sample_info(&yt_dlp_path, &global_flags, &url));
+		});
+	});
+
+	let timer = Timer::new(&dialog);
+	let refresh_timer = Rc::clone(&refresh);
+	timer.on_tick(move |_| {
+		if let Ok(result) = rx.try_recv() {
+			load_btn.enable(true);
+			match result {
+				Ok(info) => {
+					sample_status.set_label(&format!("Previewing with: {}", info.title));
+					*sample.borrow_mut() = info;
+					refresh_timer();
+				}
+				Err(e) => sample_status.set_label(&format!("Error: {}", e)),
+			}
+		}
+	});
+	timer.start(200, false);
+
+	let btn_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let ok_btn = Button::builder(&dialog).with_label("OK").build();
+	let cancel_btn = Button::builder(&dialog).with_label("Cancel").build();
+	btn_sizer.add_stretch_spacer(1);
+	btn_sizer.add(&ok_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add(&cancel_btn, 0, SizerFlag::All, 5);
+	main_sizer.add_stretch_spacer(1);
+	main_sizer.add_sizer(&btn_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	dialog.set_sizer(main_sizer, true);
+	dialog.centre();
+
+	let d_ok = dialog;
+	ok_btn.on_click(move |_| d_ok.end_modal(RET_OK));
+	let d_cancel = dialog;
+	cancel_btn.on_click(move |_| d_cancel.end_modal(RET_CANCEL));
+
+	if dialog.show_modal() == RET_OK {
+		let mut cfg = config_manager.lock().expect("Config manager lock failed");
+		for (kind, text, _) in &editors {
+			cfg.set_output_template(*kind, &text.get_value());
+		}
+		cfg.flush();
+	}
+	timer.stop();
+}
@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use crate::video_info::VideoInfo;
+
+pub const MAX_RECENT: usize = 8;
+
+fn is_reserved(c: char) -> bool { c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*') }
+
+pub fn sanitize_folder_name(name: &str) -> String {
+	let cleaned: String = name.chars().map(|c| if is_reserved(c) { '_' } else { c }).collect();
+	cleaned.trim().trim_end_matches('.').trim().to_string()
+}
+
+pub fn playlist_title(videos: &[VideoInfo]) -> Option<&str> {
+	videos.iter().find_map(|v| v.playlist_title.as_deref()).filter(|t| !t.trim().is_empty())
+}
+
+pub fn playlist_folder(base: &str, videos: &[VideoInfo]) -> Option<String> {
+	let name = sanitize_folder_name(playlist_title(videos)?);
+	(!name.is_empty()).then(|| Path::new(base).join(name).to_string_lossy().into_owned())
+}
+
+pub fn remember(recent: &mut Vec<String>, folder: &str) {
+	recent.retain(|r| r != folder);
+	recent.insert(0, folder.to_string());
+	recent.truncate(MAX_RECENT);
+}
@@ -0,0 +1,17 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::video_info::VideoInfo;
+
+pub fn load(path: &Path) -> HashSet<String> {
+	fs::read_to_string(path)
+		.map(|s| s.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+		.unwrap_or_default()
+}
+
+pub fn entry_key(info: &VideoInfo) -> Option<String> {
+	info.ie_key.as_ref().or(info.extractor_key.as_ref()).map(|ie| format!("{} {}", ie.to_lowercase(), info.id))
+}
+
+pub fn contains(archive: &HashSet<String>, info: &VideoInfo) -> bool {
+	entry_key(info).is_some_and(|key| archive.contains(&key))
+}
@@ -1,46 +1,671 @@
 use std::{
-	collections::HashMap,
+	collections::{HashMap, VecDeque},
+	fmt, fs,
+	path::{Path, PathBuf},
 	process::Child,
-	sync::{Arc, Mutex},
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+	},
+	thread,
+	time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
+use serde::{Deserialize, Serialize};
+
+use crate::{download_options::DownloadOptions, job_log::JobLog, platform, retry::RetryPolicy};
+
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct JobId(u64);
+
+impl fmt::Display for JobId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "#{}", self.0) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+	Queued,
+	FetchingInfo,
+	Running,
+	Paused,
+	Finished,
+	Failed,
+	Cancelled,
+}
+
+impl JobState {
+	pub fn is_active(self) -> bool { matches!(self, JobState::Queued | JobState::FetchingInfo | JobState::Running) }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+	pub id: JobId,
+	pub urls: Vec<String>,
+	pub options: Option<DownloadOptions>,
+	pub custom_command: Option<String>,
+	pub output_dir: Option<String>,
+	pub started_at: SystemTime,
+	pub state: JobState,
+	#[serde(default)]
+	pub resumed: bool,
+	#[serde(default)]
+	pub retry: RetryPolicy,
+	#[serde(default)]
+	pub output_files: Vec<String>,
+	#[serde(default)]
+	pub format_selector: Option<String>,
+	#[serde(default)]
+	pub info_path: Option<PathBuf>,
+}
+
+impl Job {
+	pub fn label(&self) -> String {
+		let first = self.urls.first().map(String::as_str).unwrap_or_default();
+		if self.urls.len() > 1 { format!("{} (+{} more)", first, self.urls.len() - 1) } else { first.to_string() }
+	}
+}
+
 #[derive(Clone)]
 pub struct DownloadManager {
-	history: Arc<Mutex<HashMap<String, String>>>,
-	active_tasks: Arc<Mutex<HashMap<String, Arc<Mutex<Child>>>>>,
+	next_id: Arc<AtomicU64>,
+	jobs: Arc<Mutex<HashMap<JobId, Job>>>,
+	history: Arc<Mutex<HashMap<JobId, JobLog>>>,
+	active_tasks: Arc<Mutex<HashMap<JobId, Arc<Mutex<Child>>>>>,
+	queue: Arc<Mutex<VecDeque<JobId>>>,
+	max_concurrent: Arc<AtomicUsize>,
+	queue_running: Arc<AtomicBool>,
+	state_path: Arc<Mutex<PathBuf>>,
+	log_dir: PathBuf,
 }
 
 impl DownloadManager {
-	pub fn new() -> Self {
-		Self { history: Arc::new(Mutex::new(HashMap::new())), active_tasks: Arc::new(Mutex::new(HashMap::new())) }
+	pub fn new(state_path: PathBuf, log_dir: PathBuf) -> Self {
+		Self {
+			next_id: Arc::new(AtomicU64::new(1)),
+			jobs: Arc::new(Mutex::new(HashMap::new())),
+			history: Arc::new(Mutex::new(HashMap::new())),
+			active_tasks: Arc::new(Mutex::new(HashMap::new())),
+			queue: Arc::new(Mutex::new(VecDeque::new())),
+			max_concurrent: Arc::new(AtomicUsize::new(1)),
+			queue_running: Arc::new(AtomicBool::new(true)),
+			state_path: Arc::new(Mutex::new(state_path)),
+			log_dir,
+		}
 	}
 
-	pub fn register_task(&self, tag: String, child: Child) -> Arc<Mutex<Child>> {
+	pub fn create_job(
+		&self,
+		urls: Vec<String>,
+		options: Option<DownloadOptions>,
+		custom_command: Option<String>,
+		output_dir: Option<String>,
+		state: JobState,
+	) -> JobId {
+		let id = JobId(self.next_id.fetch_add(1, Ordering::Relaxed));
+		let job = Job {
+			id,
+			urls,
+			options,
+			custom_command,
+			output_dir,
+			started_at: SystemTime::now(),
+			state,
+			resumed: false,
+			retry: RetryPolicy::default(),
+			output_files: Vec::new(),
+			format_selector: None,
+			info_path: None,
+		};
+		self.jobs.lock().expect("Jobs lock failed").insert(id, job);
+		id
+	}
+
+	pub fn restore_job(&self, job: Job) -> JobId {
+		let id = JobId(self.next_id.fetch_add(1, Ordering::Relaxed));
+		let state = if job.state == JobState::Paused { JobState::Paused } else { JobState::Queued };
+		let job = Job { id, state, resumed: true, ..job };
+		self.jobs.lock().expect("Jobs lock failed").insert(id, job);
+		if state == JobState::Queued {
+			self.queue.lock().expect("Queue lock failed").push_back(id);
+		}
+		self.save();
+		id
+	}
+
+	pub fn load_saved_jobs(&self) -> Vec<Job> {
+		fs::read_to_string(&*self.state_path.lock().expect("State path lock failed"))
+			.ok()
+			.and_then(|s| serde_json::from_str(&s).ok())
+			.unwrap_or_default()
+	}
+
+	pub fn discard_saved_jobs(&self) {
+		let _ = fs::remove_file(&*self.state_path.lock().expect("State path lock failed"));
+	}
+
+	fn pending_jobs(&self) -> Vec<Job> {
+		let jobs = self.jobs.lock().expect("Jobs lock failed");
+		let queue = self.queue.lock().expect("Queue lock failed");
+		let mut running: Vec<&Job> =
+			jobs.values().filter(|j| matches!(j.state, JobState::Running | JobState::Paused)).collect();
+		running.sort_by_key(|j| j.id);
+		running.into_iter().cloned().chain(queue.iter().filter_map(|id| jobs.get(id).cloned())).collect()
+	}
+
+	fn save(&self) {
+		let pending = self.pending_jobs();
+		let state_path = self.state_path.lock().expect("State path lock failed");
+		if pending.is_empty() {
+			let _ = fs::remove_file(&*state_path);
+			return;
+		}
+		if let Ok(json) = serde_json::to_string_pretty(&pending) {
+			let temp_path = state_path.with_extension("json.part");
+			if fs::write(&temp_path, json).is_ok() {
+				let _ = fs::rename(&temp_path, &*state_path);
+			}
+		}
+	}
+
+	pub fn get_job(&self, id: JobId) -> Option<Job> { self.jobs.lock().expect("Jobs lock failed").get(&id).cloned() }
+
+	pub fn set_state(&self, id: JobId, state: JobState) {
+		if let Some(job) = self.jobs.lock().expect("Jobs lock failed").get_mut(&id) {
+			job.state = state;
+		}
+		self.save();
+	}
+
+	pub fn update_job(&self, id: JobId, f: impl FnOnce(&mut Job)) {
+		if let Some(job) = self.jobs.lock().expect("Jobs lock failed").get_mut(&id) {
+			f(job);
+		}
+		self.save();
+	}
+
+	pub fn enqueue(&self, id: JobId) {
+		self.set_state(id, JobState::Queued);
+		self.queue.lock().expect("Queue lock failed").push_back(id);
+		self.save();
+	}
+
+	pub fn dequeue(&self, id: JobId) -> bool {
+		let removed = {
+			let mut queue = self.queue.lock().expect("Queue lock failed");
+			queue.iter().position(|q| *q == id).and_then(|pos| queue.remove(pos)).is_some()
+		};
+		if removed {
+			self.set_state(id, JobState::Cancelled);
+			self.history.lock().expect("History lock failed").remove(&id);
+		}
+		removed
+	}
+
+	pub fn queued_jobs(&self) -> Vec<JobId> { self.queue.lock().expect("Queue lock failed").iter().copied().collect() }
+
+	pub fn move_queued(&self, id: JobId, offset: isize) -> bool {
+		{
+			let mut queue = self.queue.lock().expect("Queue lock failed");
+			let Some(pos) = queue.iter().position(|q| *q == id) else {
+				return false;
+			};
+			let target = pos.saturating_add_signed(offset).min(queue.len() - 1);
+			if target == pos {
+				return false;
+			}
+			queue.remove(pos);
+			queue.insert(target, id);
+		}
+		self.save();
+		true
+	}
+
+	pub fn set_max_concurrent(&self, max: usize) { self.max_concurrent.store(max.max(1), Ordering::Relaxed); }
+
+	pub fn set_queue_running(&self, running: bool) { self.queue_running.store(running, Ordering::Relaxed); }
+
+	pub fn is_queue_running(&self) -> bool { self.queue_running.load(Ordering::Relaxed) }
+
+	pub fn take_ready_jobs(&self) -> Vec<JobId> {
+		if !self.is_queue_running() {
+			return Vec::new();
+		}
+		let ready: Vec<JobId> = {
+			let mut jobs = self.jobs.lock().expect("Jobs lock failed");
+			let mut queue = self.queue.lock().expect("Queue lock failed");
+			let running = jobs.values().filter(|j| j.state == JobState::Running).count();
+			let free = self.max_concurrent.load(Ordering::Relaxed).saturating_sub(running).min(queue.len());
+			let ready: Vec<JobId> = queue.drain(..free).collect();
+			for id in &ready {
+				if let Some(job) = jobs.get_mut(id) {
+					job.state = JobState::Running;
+				}
+			}
+			ready
+		};
+		if !ready.is_empty() {
+			self.save();
+		}
+		ready
+	}
+
+	pub fn register_task(&self, id: JobId, child: Child) -> Arc<Mutex<Child>> {
 		let shared = Arc::new(Mutex::new(child));
-		self.active_tasks.lock().expect("Active tasks lock failed").insert(tag, shared.clone());
+		self.active_tasks.lock().expect("Active tasks lock failed").insert(id, shared.clone());
 		shared
 	}
 
-	pub fn unregister_task(&self, tag: &str) {
-		self.active_tasks.lock().expect("Active tasks lock failed").remove(tag);
+	pub fn unregister_task(&self, id: JobId) {
+		self.active_tasks.lock().expect("Active tasks lock failed").remove(&id);
+	}
+
+	pub fn cancel_task(&self, id: JobId, keep_partial_files: bool) {
+		let child = self.active_tasks.lock().expect("Active tasks lock failed").get(&id).cloned();
+		if let Some(child) = child {
+			self.set_state(id, JobState::Cancelled);
+			self.stop_child(id, child, !keep_partial_files);
+		}
+	}
+
+	fn stop_child(&self, id: JobId, child: Arc<Mutex<Child>>, remove_partial_files: bool) {
+		let pid = child.lock().expect("Child lock failed").id();
+		let grace = if platform::interrupt_tree(pid) { STOP_GRACE_PERIOD } else { Duration::ZERO };
+		let dm = self.clone();
+		thread::spawn(move || {
+			let deadline = Instant::now() + grace;
+			let running = || child.lock().expect("Child lock failed").try_wait().is_ok_and(|s| s.is_none());
+			while Instant::now() < deadline && running() {
+				thread::sleep(Duration::from_millis(100));
+			}
+			platform::kill_tree(pid);
+			if running() {
+				let mut child = child.lock().expect("Child lock failed");
+				let _ = child.kill();
+				let _ = child.wait();
+			}
+			let deadline = Instant::now() + STOP_GRACE_PERIOD;
+			while Instant::now() < deadline && platform::is_tree_alive(pid) {
+				thread::sleep(Duration::from_millis(100));
+			}
+			if remove_partial_files {
+				dm.remove_partial_files(id);
+			}
+		});
+	}
+
+	pub fn record_output_file(&self, id: JobId, path: &str) {
+		if self.get_job(id).is_some_and(|j| !j.output_files.iter().any(|f| f == path)) {
+			self.update_job(id, |j| j.output_files.push(path.to_string()));
+		}
+	}
+
+	pub fn remove_partial_files(&self, id: JobId) {
+		let Some(job) = self.get_job(id) else {
+			return;
+		};
+		let base = job.output_dir.map(PathBuf::from).unwrap_or_default();
+		for file in &job.output_files {
+			let path = base.join(file);
+			let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+			let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+				continue;
+			};
+			let temp_name = match name.rsplit_once('.') {
+				Some((stem, ext)) => format!("{}.temp.{}", stem, ext),
+				None => format!("{}.temp", name),
+			};
+			let is_format_stream = name.rsplit('.').nth(1).is_some_and(|s| {
+				s.strip_prefix('f').is_some_and(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit()))
+			});
+			let is_partial = |entry: &str| {
+				entry == format!("{}.part", name)
+					|| entry == format!("{}.ytdl", name)
+					|| entry.starts_with(&format!("{}.part-Frag", name))
+					|| entry == temp_name
+					|| (is_format_stream && entry == name)
+			};
+			for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+				if is_partial(&entry.file_name().to_string_lossy()) {
+					let _ = fs::remove_file(entry.path());
+				}
+			}
+		}
+	}
+
+	pub fn pause_task(&self, id: JobId) -> bool {
+		let was_queued = {
+			let mut queue = self.queue.lock().expect("Queue lock failed");
+			queue.iter().position(|q| *q == id).and_then(|pos| queue.remove(pos)).is_some()
+		};
+		if was_queued {
+			self.set_state(id, JobState::Paused);
+			return true;
+		}
+		let Some(child) = self.active_tasks.lock().expect("Active tasks lock failed").get(&id).cloned() else {
+			if self.get_job(id).is_some_and(|j| j.state == JobState::Running) {
+				self.set_state(id, JobState::Paused);
+				return true;
+			}
+			return false;
+		};
+		self.set_state(id, JobState::Paused);
+		self.stop_child(id, child, false);
+		true
 	}
 
-	pub fn cancel_task(&self, tag: &str) {
-		if let Some(child) = self.active_tasks.lock().expect("Active tasks lock failed").get(tag) {
-			let _ = child.lock().expect("Child lock failed").kill();
+	pub fn resume_task(&self, id: JobId) -> bool {
+		if self.has_task(id) {
+			return false;
+		}
+		{
+			let mut jobs = self.jobs.lock().expect("Jobs lock failed");
+			let Some(job) = jobs.get_mut(&id).filter(|j| j.state == JobState::Paused) else {
+				return false;
+			};
+			job.state = JobState::Queued;
+			job.resumed = true;
+			self.queue.lock().expect("Queue lock failed").push_front(id);
 		}
+		self.save();
+		true
+	}
+
+	pub fn pause_all(&self) -> Vec<JobId> {
+		let mut ids: Vec<JobId> = self
+			.jobs
+			.lock()
+			.expect("Jobs lock failed")
+			.values()
+			.filter(|j| matches!(j.state, JobState::Queued | JobState::Running))
+			.map(|j| j.id)
+			.collect();
+		ids.sort();
+		ids.retain(|id| self.pause_task(*id));
+		ids
 	}
 
-	pub fn append_output(&self, tag: &str, output: &str) {
+	pub fn resume_all(&self) -> Vec<JobId> {
+		let mut ids: Vec<JobId> = self
+			.jobs
+			.lock()
+			.expect("Jobs lock failed")
+			.values()
+			.filter(|j| j.state == JobState::Paused)
+			.map(|j| j.id)
+			.collect();
+		ids.sort_by(|a, b| b.cmp(a));
+		ids.retain(|id| self.resume_task(*id));
+		ids.reverse();
+		ids
+	}
+
+	pub fn append_output(&self, id: JobId, output: &str) {
+		let cancelled = self.get_job(id).is_some_and(|j| j.state == JobState::Cancelled);
 		let mut history = self.history.lock().expect("History lock failed");
-		history.entry(tag.to_string()).or_default().push_str(output);
+		if cancelled && !history.contains_key(&id) {
+			return;
+		}
+		history.entry(id).or_insert_with(|| JobLog::new(self.job_log_path(id))).append(output);
+	}
+
+	pub fn get_output(&self, id: JobId) -> String {
+		self.history.lock().expect("History lock failed").get(&id).map(|log| log.tail()).unwrap_or_default()
+	}
+
+	pub fn get_log_path(&self, id: JobId) -> Option<PathBuf> {
+		self.history.lock().expect("History lock failed").get(&id).map(|log| log.path().to_path_buf())
+	}
+
+	pub fn job_info_path(&self, id: JobId) -> PathBuf { self.job_log_path(id).with_extension("info.jsonl") }
+
+	fn job_log_path(&self, id: JobId) -> PathBuf {
+		let started = self
+			.get_job(id)
+			.and_then(|j| j.started_at.duration_since(UNIX_EPOCH).ok())
+			.map(|d| d.as_secs())
+			.unwrap_or_default();
+		self.log_dir.join(format!("job-{}-{}.log", started, id.0))
+	}
+
+	pub fn has_task(&self, id: JobId) -> bool {
+		self.active_tasks.lock().expect("Active tasks lock failed").contains_key(&id)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn manager(name: &str) -> DownloadManager {
+		let dir = std::env::temp_dir().join(format!("tubex-queue-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).expect("Create test directory");
+		DownloadManager::new(dir.join("Tubex.queue.json"), dir.join("logs"))
+	}
+
+	fn queue_jobs(dm: &DownloadManager, count: usize) -> Vec<JobId> {
+		(0..count)
+			.map(|i| {
+				let id = dm.create_job(vec![format!("https://example.com/{}", i)], None, None, None, JobState::Queued);
+				dm.enqueue(id);
+				id
+			})
+			.collect()
+	}
+
+	#[test]
+	fn job_ids_are_unique() {
+		let dm = manager("ids");
+		let ids = queue_jobs(&dm, 3);
+		assert!(ids[0] < ids[1] && ids[1] < ids[2]);
+		assert_eq!(dm.get_job(ids[1]).unwrap().urls, ["https://example.com/1"]);
+	}
+
+	#[test]
+	fn respects_concurrency_limit() {
+		let dm = manager("limit");
+		let ids = queue_jobs(&dm, 4);
+		dm.set_max_concurrent(2);
+
+		assert_eq!(dm.take_ready_jobs(), ids[..2]);
+		assert!(dm.take_ready_jobs().is_empty());
+		assert_eq!(dm.get_job(ids[0]).unwrap().state, JobState::Running);
+
+		dm.set_state(ids[0], JobState::Finished);
+		assert_eq!(dm.take_ready_jobs(), [ids[2]]);
+		assert_eq!(dm.queued_jobs(), [ids[3]]);
 	}
 
-	pub fn get_output(&self, tag: &str) -> String {
-		self.history.lock().expect("History lock failed").get(tag).cloned().unwrap_or_default()
+	#[test]
+	fn stopped_queue_starts_nothing() {
+		let dm = manager("stopped");
+		let ids = queue_jobs(&dm, 2);
+		dm.set_queue_running(false);
+		assert!(dm.take_ready_jobs().is_empty());
+		dm.set_queue_running(true);
+		assert_eq!(dm.take_ready_jobs(), [ids[0]]);
+	}
+
+	#[test]
+	fn reorders_queued_jobs() {
+		let dm = manager("reorder");
+		let ids = queue_jobs(&dm, 4);
+
+		assert!(dm.move_queued(ids[3], isize::MIN));
+		assert_eq!(dm.queued_jobs(), [ids[3], ids[0], ids[1], ids[2]]);
+		assert!(dm.move_queued(ids[0], 1));
+		assert_eq!(dm.queued_jobs(), [ids[3], ids[1], ids[0], ids[2]]);
+		assert!(dm.move_queued(ids[2], -1));
+		assert_eq!(dm.queued_jobs(), [ids[3], ids[1], ids[2], ids[0]]);
+
+		assert!(!dm.move_queued(ids[3], -1));
+		assert!(!dm.move_queued(ids[0], 1));
+	}
+
+	#[test]
+	fn dequeue_cancels_only_queued_jobs() {
+		let dm = manager("dequeue");
+		let ids = queue_jobs(&dm, 2);
+		assert_eq!(dm.take_ready_jobs(), [ids[0]]);
+
+		assert!(!dm.dequeue(ids[0]));
+		assert!(dm.dequeue(ids[1]));
+		assert_eq!(dm.get_job(ids[1]).unwrap().state, JobState::Cancelled);
+		assert!(dm.queued_jobs().is_empty());
+		assert!(!dm.move_queued(ids[1], 1));
+	}
+
+	#[test]
+	fn dequeue_drops_the_job_log() {
+		let dm = manager("dequeue-log");
+		let ids = queue_jobs(&dm, 2);
+		dm.append_output(ids[0], "Queued");
+		dm.append_output(ids[1], "Queued");
+		let path = dm.get_log_path(ids[0]).unwrap();
+
+		assert!(dm.dequeue(ids[0]));
+		assert_eq!(dm.get_output(ids[0]), "");
+		assert_eq!(dm.get_log_path(ids[0]), None);
+		dm.append_output(ids[0], "Error: Removed from queue");
+		assert_eq!(dm.get_log_path(ids[0]), None);
+		assert_eq!(fs::read_to_string(path).unwrap(), "Queued\n");
+		assert_eq!(dm.get_output(ids[1]), "Queued\n");
+	}
+
+	#[test]
+	fn pausing_and_resuming_a_queued_job() {
+		let dm = manager("pause-queued");
+		let ids = queue_jobs(&dm, 3);
+
+		assert!(dm.pause_task(ids[1]));
+		assert_eq!(dm.get_job(ids[1]).unwrap().state, JobState::Paused);
+		assert_eq!(dm.queued_jobs(), [ids[0], ids[2]]);
+		assert!(!dm.pause_task(ids[1]));
+
+		assert!(dm.resume_task(ids[1]));
+		assert_eq!(dm.queued_jobs(), [ids[1], ids[0], ids[2]]);
+		assert!(dm.get_job(ids[1]).unwrap().resumed);
+		assert!(!dm.resume_task(ids[1]));
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn pausing_a_running_job_stops_its_process() {
+		let dm = manager("pause-running");
+		let ids = queue_jobs(&dm, 1);
+		assert_eq!(dm.take_ready_jobs(), ids);
+		let mut cmd = std::process::Command::new("sleep");
+		cmd.arg("30");
+		platform::isolate_process_group(&mut cmd);
+		let child = dm.register_task(ids[0], cmd.spawn().unwrap());
+
+		assert!(dm.pause_task(ids[0]));
+		assert_eq!(dm.get_job(ids[0]).unwrap().state, JobState::Paused);
+		assert!(!dm.resume_task(ids[0]));
+		assert!(child.lock().unwrap().wait().is_ok());
+
+		dm.unregister_task(ids[0]);
+		assert!(dm.resume_task(ids[0]));
+		assert_eq!(dm.queued_jobs(), ids);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn cancelling_kills_processes_that_outlive_the_leader() {
+		let dm = manager("cancel-group");
+		let ids = queue_jobs(&dm, 1);
+		assert_eq!(dm.take_ready_jobs(), ids);
+		let mut cmd = std::process::Command::new("sh");
+		cmd.arg("-c").arg("(trap '' INT; exec sleep 30) & sleep 30");
+		platform::isolate_process_group(&mut cmd);
+		let child = cmd.spawn().unwrap();
+		let pid = child.id();
+		dm.register_task(ids[0], child);
+		thread::sleep(Duration::from_millis(300));
+
+		dm.cancel_task(ids[0], true);
+		let deadline = Instant::now() + STOP_GRACE_PERIOD * 3;
+		while Instant::now() < deadline && platform::is_tree_alive(pid) {
+			thread::sleep(Duration::from_millis(100));
+		}
+		assert!(!platform::is_tree_alive(pid));
+	}
+
+	#[test]
+	fn pause_all_and_resume_all_leave_finished_jobs_alone() {
+		let dm = manager("pause-all");
+		let ids = queue_jobs(&dm, 6);
+		dm.set_max_concurrent(4);
+		assert_eq!(dm.take_ready_jobs(), ids[..4]);
+		dm.set_state(ids[1], JobState::Finished);
+		dm.set_state(ids[2], JobState::Failed);
+		dm.set_state(ids[3], JobState::Cancelled);
+		let states = |dm: &DownloadManager| ids.iter().map(|id| dm.get_job(*id).unwrap().state).collect::<Vec<_>>();
+
+		assert_eq!(dm.pause_all(), [ids[0], ids[4], ids[5]]);
+		assert_eq!(
+			states(&dm),
+			[
+				JobState::Paused,
+				JobState::Finished,
+				JobState::Failed,
+				JobState::Cancelled,
+				JobState::Paused,
+				JobState::Paused
+			]
+		);
+		assert!(dm.queued_jobs().is_empty());
+		assert!(dm.pause_all().is_empty());
+
+		assert_eq!(dm.resume_all(), [ids[0], ids[4], ids[5]]);
+		assert_eq!(
+			states(&dm),
+			[
+				JobState::Queued,
+				JobState::Finished,
+				JobState::Failed,
+				JobState::Cancelled,
+				JobState::Queued,
+				JobState::Queued
+			]
+		);
+		assert_eq!(dm.queued_jobs(), [ids[0], ids[4], ids[5]]);
+		assert!(dm.resume_all().is_empty());
+	}
+
+	#[test]
+	fn saved_queue_restores_after_restart() {
+		let dm = manager("restore");
+		let ids = queue_jobs(&dm, 4);
+		assert_eq!(dm.take_ready_jobs(), [ids[0]]);
+		assert!(dm.pause_task(ids[1]));
+		assert!(dm.dequeue(ids[3]));
+		dm.update_job(ids[2], |j| j.output_dir = Some("/downloads".into()));
+
+		let restarted = DownloadManager::new(dm.state_path.lock().unwrap().clone(), dm.log_dir.clone());
+		let saved = restarted.load_saved_jobs();
+		let urls: Vec<&str> = saved.iter().map(|j| j.urls[0].as_str()).collect();
+		assert_eq!(urls, ["https://example.com/0", "https://example.com/1", "https://example.com/2"]);
+
+		let restored: Vec<JobId> = saved.into_iter().map(|j| restarted.restore_job(j)).collect();
+		let states: Vec<JobState> = restored.iter().map(|id| restarted.get_job(*id).unwrap().state).collect();
+		assert_eq!(states, [JobState::Queued, JobState::Paused, JobState::Queued]);
+		assert!(restored.iter().all(|id| restarted.get_job(*id).unwrap().resumed));
+		assert_eq!(restarted.queued_jobs(), [restored[0], restored[2]]);
+		assert_eq!(restarted.get_job(restored[2]).unwrap().output_dir.as_deref(), Some("/downloads"));
+
+		restarted.discard_saved_jobs();
+		assert!(restarted.load_saved_jobs().is_empty());
 	}
 
-	pub fn has_task(&self, tag: &str) -> bool {
-		self.active_tasks.lock().expect("Active tasks lock failed").contains_key(tag)
+	#[test]
+	fn save_removes_state_file_when_nothing_is_pending() {
+		let dm = manager("empty");
+		let ids = queue_jobs(&dm, 1);
+		assert!(dm.state_path.lock().unwrap().exists());
+		dm.dequeue(ids[0]);
+		assert!(!dm.state_path.lock().unwrap().exists());
 	}
 }
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use crate::presets::FormatPreset;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DownloadMode {
+	Single { video_format: Option<String>, audio_formats: Vec<String> },
+	Playlist { indices: Vec<usize>, video_format: Option<String>, audio_formats: Vec<String> },
+}
+
+impl DownloadMode {
+	pub fn formats(&self) -> (Option<&String>, &[String]) {
+		match self {
+			DownloadMode::Single { video_format, audio_formats }
+			| DownloadMode::Playlist { video_format, audio_formats, .. } => (video_format.as_ref(), audio_formats),
+		}
+	}
+}
+
+pub const AUDIO_CODECS: [&str; 5] = ["mp3", "m4a", "opus", "flac", "wav"];
+pub const AUDIO_QUALITIES: [&str; 6] = ["best", "320K", "256K", "192K", "128K", "96K"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioExtraction {
+	pub codec: String,
+	pub quality: String,
+}
+
+impl AudioExtraction {
+	pub fn args(&self) -> Vec<String> {
+		let quality = if self.quality == "best" { "0" } else { self.quality.as_str() };
+		["-x", "--audio-format", self.codec.as_str(), "--audio-quality", quality].map(String::from).to_vec()
+	}
+
+	pub fn to_config_value(&self) -> String { format!("{}:{}", self.codec, self.quality) }
+
+	pub fn from_config_value(value: &str) -> Option<Self> {
+		let (codec, quality) = value.trim().split_once(':')?;
+		AUDIO_CODECS.contains(&codec).then(|| Self { codec: codec.to_string(), quality: quality.to_string() })
+	}
+}
+
+pub const SUBTITLE_FORMATS: [&str; 4] = ["original", "srt", "vtt", "ass"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleOptions {
+	pub languages: Vec<String>,
+	pub manual: bool,
+	pub automatic: bool,
+	pub format: String,
+	pub embed: bool,
+}
+
+impl SubtitleOptions {
+	pub fn args(&self) -> Vec<String> {
+		let mut args = Vec::new();
+		if self.manual {
+			args.push("--write-subs".to_string());
+		}
+		if self.automatic {
+			args.push("--write-auto-subs".to_string());
+		}
+		args.push("--sub-langs".to_string());
+		args.push(self.languages.join(","));
+		if self.format != "original" {
+			args.push("--convert-subs".to_string());
+			args.push(self.format.clone());
+		}
+		if self.embed {
+			args.push("--embed-subs".to_string());
+		}
+		args
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeRange {
+	pub start: f64,
+	pub end: f64,
+}
+
+impl TimeRange {
+	pub fn parse(text: &str) -> Result<Self, String> {
+		let (start, end) = text.split_once('-').ok_or_else(|| format!("\"{}\" is not a start-end range", text))?;
+		let start = parse_timestamp(start).ok_or_else(|| format!("Invalid start time in \"{}\"", text))?;
+		let end = parse_timestamp(end).ok_or_else(|| format!("Invalid end time in \"{}\"", text))?;
+		if end <= start {
+			return Err(format!("The end of \"{}\" is not after its start", text));
+		}
+		Ok(Self { start, end })
+	}
+
+	pub fn to_section(self) -> String { format!("*{}-{}", format_position(self.start), format_position(self.end)) }
+}
+
+pub fn parse_timestamp(text: &str) -> Option<f64> {
+	let parts: Vec<&str> = text.trim().split(':').collect();
+	if parts.len() > 3 {
+		return None;
+	}
+	parts.iter().try_fold(0.0, |acc, part| {
+		part.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0).map(|v| acc * 60.0 + v)
+	})
+}
+
+pub fn format_position(secs: f64) -> String {
+	let total_ms = (secs * 1000.0).round() as u64;
+	let (whole, ms) = (total_ms / 1000, total_ms % 1000);
+	let (hours, minutes, seconds) = (whole / 3600, whole % 3600 / 60, whole % 60);
+	if ms > 0 {
+		format!("{}:{:02}:{:02}.{:03}", hours, minutes, seconds, ms)
+	} else {
+		format!("{}:{:02}:{:02}", hours, minutes, seconds)
+	}
+}
+
+pub fn parse_time_ranges(text: &str) -> Result<Vec<TimeRange>, String> {
+	text.split(['\n', ',']).map(str::trim).filter(|r| !r.is_empty()).map(TimeRange::parse).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadSections {
+	pub ranges: Vec<TimeRange>,
+	pub precise: bool,
+}
+
+impl DownloadSections {
+	pub fn args(&self) -> Vec<String> {
+		let mut args = Vec::new();
+		for range in &self.ranges {
+			args.push("--download-sections".to_string());
+			args.push(range.to_section());
+		}
+		if self.precise {
+			args.push("--force-keyframes-at-cuts".to_string());
+		}
+		args
+	}
+}
+
+pub const SPONSORBLOCK_CATEGORIES: [&str; 6] =
+	["sponsor", "intro", "outro", "selfpromo", "interaction", "music_offtopic"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SponsorBlockOptions {
+	pub mark: Vec<String>,
+	pub remove: Vec<String>,
+}
+
+impl SponsorBlockOptions {
+	pub fn is_empty(&self) -> bool { self.mark.is_empty() && self.remove.is_empty() }
+
+	pub fn args(&self, api_url: &str) -> Vec<String> {
+		let mut args = Vec::new();
+		if !self.mark.is_empty() {
+			args.push("--sponsorblock-mark".to_string());
+			args.push(self.mark.join(","));
+		}
+		if !self.remove.is_empty() {
+			args.push("--sponsorblock-remove".to_string());
+			args.push(self.remove.join(","));
+		}
+		if !self.is_empty() && !api_url.trim().is_empty() {
+			args.push("--sponsorblock-api".to_string());
+			args.push(api_url.trim().to_string());
+		}
+		args
+	}
+}
+
+pub const THUMBNAIL_FORMATS: [&str; 4] = ["original", "jpg", "png", "webp"];
+const SQUARE_THUMBNAIL_ARGS: &str =
+	"ThumbnailsConvertor+FFmpeg_o:-c:v mjpeg -vf crop=\"'if(gt(ih,iw),iw,ih)':'if(gt(iw,ih),ih,iw)'\"";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataOptions {
+	pub embed_thumbnail: bool,
+	pub thumbnail_format: Option<String>,
+	pub square_thumbnail: bool,
+	pub embed_metadata: bool,
+	pub write_info_json: bool,
+	pub write_description: bool,
+}
+
+impl MetadataOptions {
+	pub fn is_empty(&self) -> bool {
+		!(self.embed_thumbnail || self.embed_metadata || self.write_info_json || self.write_description)
+	}
+
+	pub fn args(&self) -> Vec<String> {
+		let mut args = Vec::new();
+		if self.embed_thumbnail {
+			args.push("--embed-thumbnail".to_string());
+			if self.square_thumbnail {
+				args.push("--convert-thumbnails".to_string());
+				args.push("jpg".to_string());
+				args.push("--ppa".to_string());
+				args.push(SQUARE_THUMBNAIL_ARGS.to_string());
+			} else if let Some(format) = &self.thumbnail_format {
+				args.push("--convert-thumbnails".to_string());
+				args.push(format.clone());
+			}
+		}
+		if self.embed_metadata {
+			args.push("--embed-metadata".to_string());
+		}
+		if self.write_info_json {
+			args.push("--write-info-json".to_string());
+		}
+		if self.write_description {
+			args.push("--write-description".to_string());
+		}
+		args
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadOptions {
+	pub mode: DownloadMode,
+	pub add_chapters: bool,
+	pub multi_audio: bool,
+	pub preferred_languages: Vec<String>,
+	#[serde(default)]
+	pub preset: Option<FormatPreset>,
+	#[serde(default)]
+	pub extract_audio: Option<AudioExtraction>,
+	#[serde(default)]
+	pub subtitles: Option<SubtitleOptions>,
+	#[serde(default)]
+	pub sections: Option<DownloadSections>,
+	#[serde(default)]
+	pub sponsorblock: Option<SponsorBlockOptions>,
+	#[serde(default)]
+	pub metadata: Option<MetadataOptions>,
+	#[serde(default)]
+	pub destination: Option<String>,
+}
+
+impl DownloadOptions {
+	pub fn from_preset(preset: FormatPreset, mode: DownloadMode) -> Self {
+		Self {
+			mode,
+			add_chapters: true,
+			multi_audio: false,
+			preferred_languages: Vec::new(),
+			extract_audio: preset.extract_audio.clone(),
+			subtitles: None,
+			sections: None,
+			sponsorblock: None,
+			metadata: None,
+			destination: None,
+			preset: Some(preset),
+		}
+	}
+}
+
+#[cfg(test)]
+impl DownloadOptions {
+	pub fn for_mode(mode: DownloadMode) -> Self {
+		Self {
+			mode,
+			add_chapters: false,
+			multi_audio: false,
+			preferred_languages: Vec::new(),
+			preset: None,
+			extract_audio: None,
+			subtitles: None,
+			sections: None,
+			sponsorblock: None,
+			metadata: None,
+			destination: None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_timestamps() {
+		assert_eq!(parse_timestamp("90"), Some(90.0));
+		assert_eq!(parse_timestamp("1:30"), Some(90.0));
+		assert_eq!(parse_timestamp(" 1:02:03.5 "), Some(3723.5));
+		assert_eq!(parse_timestamp("1:2:3:4"), None);
+		assert_eq!(parse_timestamp("1:-5"), None);
+		assert_eq!(parse_timestamp("abc"), None);
+		assert_eq!(parse_timestamp(""), None);
+	}
+
+	#[test]
+	fn formats_positions() {
+		assert_eq!(format_position(0.0), "0:00:00");
+		assert_eq!(format_position(3723.0), "1:02:03");
+		assert_eq!(format_position(90.25), "0:01:30.250");
+		assert_eq!(format_position(59.9996), "0:01:00");
+		assert_eq!(format_position(3599.9999), "1:00:00");
+		assert_eq!(format_position(3599.9994), "0:59:59.999");
+	}
+
+	#[test]
+	fn parses_time_ranges() {
+		assert_eq!(TimeRange::parse("1:00-1:30.5"), Ok(TimeRange { start: 60.0, end: 90.5 }));
+		assert_eq!(TimeRange::parse("1:00-1:30.5").unwrap().to_section(), "*0:01:00-0:01:30.500");
+		assert!(TimeRange::parse("1:00").is_err());
+		assert!(TimeRange::parse("2:00-1:00").is_err());
+		assert!(TimeRange::parse("1:00-1:00").is_err());
+		assert!(TimeRange::parse("x-1:00").is_err());
+
+		let ranges = parse_time_ranges("0:10-0:20, 1:00-2:00\n\n 3:00-3:30 ").unwrap();
+		assert_eq!(ranges.len(), 3);
+		assert_eq!(ranges[2], TimeRange { start: 180.0, end: 210.0 });
+		assert_eq!(parse_time_ranges("  "), Ok(Vec::new()));
+		assert!(parse_time_ranges("0:10-0:20, 0:30").is_err());
+	}
+}
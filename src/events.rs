@@ -1,16 +1,23 @@
-use crate::video_info::VideoInfo;
+use crate::{download_manager::JobId, history::HistoryEntry, progress::ProgressInfo, video_info::VideoInfo};
 
 #[derive(Debug)]
 pub enum AppEvent {
-	Status(String, String),
-	Output(String, String),
-	Finished(String),
-	Error(String, String),
-	ShowOptions(String, Vec<VideoInfo>),
-	ShowOptionsForMultipleUrls(Vec<String>, Box<VideoInfo>),
+	Status(JobId, String),
+	Output(JobId, String),
+	Progress(JobId, ProgressInfo),
+	Finished(JobId),
+	Error(JobId, String),
+	ShowOptions(JobId, String, Vec<VideoInfo>),
+	ShowOptionsForMultipleUrls(JobId, Vec<String>, Box<VideoInfo>),
+	QueueChanged,
+	Paused(JobId),
+	Resumed(JobId),
+	Retrying(JobId, String),
 	StartupCheck,
+	OfferResume,
 	StartupResult(bool, bool),
-	DownloadComplete(Result<(), String>),
+	DownloadComplete(Result<Vec<String>, String>),
 	DownloadProgress(String, i32),
 	RequestFetch(String),
+	Redownload(Box<HistoryEntry>),
 }
@@ -0,0 +1,155 @@
+use crate::download_options::DownloadOptions;
+
+fn base_format(format_id: &str) -> &str { format_id.split('-').next().unwrap_or(format_id) }
+
+fn is_safe_filter_value(value: &str) -> bool {
+	!value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn selected_formats(options: &DownloadOptions) -> (Option<&String>, &[String]) {
+	let (video, audio) = options.mode.formats();
+	if options.extract_audio.is_some() { (None, audio) } else { (video, audio) }
+}
+
+fn language_chain(options: &DownloadOptions) -> Option<String> {
+	let (video, audio) = selected_formats(options);
+	if audio.is_empty()
+		|| options.preferred_languages.len() != audio.len()
+		|| !options.preferred_languages.iter().all(|l| is_safe_filter_value(l))
+	{
+		return None;
+	}
+	let video = video.map(|v| format!("bv[format_id^='{}']", base_format(v)));
+	let audio = options
+		.preferred_languages
+		.iter()
+		.zip(audio)
+		.map(|(lang, a)| format!("ba[language='{}'][format_id^='{}']", lang, base_format(a)));
+	Some(video.into_iter().chain(audio).collect::<Vec<_>>().join("+"))
+}
+
+fn exact_chain(options: &DownloadOptions) -> Option<String> {
+	let (video, audio) = selected_formats(options);
+	let parts: Vec<&str> = video.map(String::as_str).into_iter().chain(audio.iter().map(String::as_str)).collect();
+	(!parts.is_empty()).then(|| parts.join("+"))
+}
+
+fn fuzzy_chain(options: &DownloadOptions) -> Option<String> {
+	let (video, audio) = selected_formats(options);
+	let video = video.map(|_| if audio.is_empty() { "b".to_string() } else { "bv".to_string() });
+	let audio = audio.iter().map(|a| format!("ba[format_id^='{}']", base_format(a)));
+	let parts: Vec<String> = video.into_iter().chain(audio).collect();
+	(!parts.is_empty()).then(|| parts.join("+"))
+}
+
+pub fn build(options: &DownloadOptions) -> Option<String> {
+	if let Some(preset) = &options.preset {
+		return (!preset.format.is_empty()).then(|| preset.format.clone());
+	}
+	let Some(exact) = exact_chain(options) else {
+		return options.extract_audio.as_ref().map(|_| "ba/b".to_string());
+	};
+	let mut chains = Vec::new();
+	for chain in [language_chain(options), Some(exact), fuzzy_chain(options)].into_iter().flatten() {
+		if !chains.contains(&chain) {
+			chains.push(chain);
+		}
+	}
+	Some(chains.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		download_options::{AudioExtraction, DownloadMode},
+		presets::FormatPreset,
+	};
+
+	fn options(video: Option<&str>, audio: &[&str], languages: &[&str]) -> DownloadOptions {
+		DownloadOptions {
+			preferred_languages: languages.iter().map(|l| l.to_string()).collect(),
+			..DownloadOptions::for_mode(DownloadMode::Single {
+				video_format: video.map(String::from),
+				audio_formats: audio.iter().map(|a| a.to_string()).collect(),
+			})
+		}
+	}
+
+	#[test]
+	fn no_formats_gives_no_selector() {
+		assert_eq!(build(&options(None, &[], &[])), None);
+	}
+
+	#[test]
+	fn video_only_falls_back_to_best() {
+		assert_eq!(build(&options(Some("137"), &[], &[])).as_deref(), Some("137/b"));
+	}
+
+	#[test]
+	fn video_and_audio_without_languages() {
+		assert_eq!(
+			build(&options(Some("137"), &["251-drc"], &[])).as_deref(),
+			Some("137+251-drc/bv+ba[format_id^='251']")
+		);
+	}
+
+	#[test]
+	fn language_chain_comes_first() {
+		assert_eq!(
+			build(&options(Some("137-1"), &["251-0", "140-1"], &["en", "de"])).as_deref(),
+			Some(
+				"bv[format_id^='137']+ba[language='en'][format_id^='251']+ba[language='de'][format_id^='140']/\
+				 137-1+251-0+140-1/bv+ba[format_id^='251']+ba[format_id^='140']"
+			)
+		);
+	}
+
+	#[test]
+	fn audio_only_has_no_video_part() {
+		assert_eq!(
+			build(&options(None, &["251"], &["en"])).as_deref(),
+			Some("ba[language='en'][format_id^='251']/251/ba[format_id^='251']")
+		);
+	}
+
+	#[test]
+	fn mismatched_languages_skip_language_chain() {
+		assert_eq!(
+			build(&options(Some("137"), &["251", "140"], &["en"])).as_deref(),
+			Some("137+251+140/bv+ba[format_id^='251']+ba[format_id^='140']")
+		);
+	}
+
+	#[test]
+	fn unsafe_language_values_skip_language_chain() {
+		assert_eq!(
+			build(&options(Some("137"), &["251"], &["en']"])).as_deref(),
+			Some("137+251/bv+ba[format_id^='251']")
+		);
+	}
+
+	#[test]
+	fn duplicate_chains_are_dropped() {
+		assert_eq!(build(&options(Some("b"), &[], &[])).as_deref(), Some("b"));
+	}
+
+	#[test]
+	fn preset_format_overrides_selected_ids() {
+		let mut o = options(Some("137"), &["251"], &[]);
+		o.preset = Some(FormatPreset::new("Opus", "ba/b", "acodec:opus"));
+		assert_eq!(build(&o).as_deref(), Some("ba/b"));
+		o.preset = Some(FormatPreset::new("Sort only", "", "res:720"));
+		assert_eq!(build(&o), None);
+	}
+
+	#[test]
+	fn audio_extraction_ignores_video_format() {
+		let mut o = options(Some("137"), &["251"], &["en"]);
+		o.extract_audio = Some(AudioExtraction { codec: "mp3".into(), quality: "192K".into() });
+		assert_eq!(build(&o).as_deref(), Some("ba[language='en'][format_id^='251']/251/ba[format_id^='251']"));
+		let mut o = options(Some("137"), &[], &[]);
+		o.extract_audio = Some(AudioExtraction { codec: "flac".into(), quality: "best".into() });
+		assert_eq!(build(&o).as_deref(), Some("ba/b"));
+	}
+}
@@ -0,0 +1,285 @@
+use std::{
+	collections::HashSet,
+	fs::{self, OpenOptions},
+	io::Write,
+	path::{Path, PathBuf},
+	sync::{
+		Arc, Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+	download_manager::Job,
+	download_options::{DownloadMode, DownloadOptions},
+};
+
+pub const PRINT_TEMPLATE: &str =
+	"after_move:%(.{webpage_url,title,uploader,duration,filepath,filesize,filesize_approx,format_id})j";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+	#[serde(alias = "url", deserialize_with = "url_list")]
+	pub urls: Vec<String>,
+	pub title: Option<String>,
+	pub uploader: Option<String>,
+	pub format_selector: Option<String>,
+	pub output_files: Vec<String>,
+	pub size_bytes: Option<u64>,
+	pub duration_secs: Option<f64>,
+	pub finished_at: u64,
+	pub exit_status: String,
+	pub output_dir: Option<String>,
+	pub options: Option<DownloadOptions>,
+	pub custom_command: Option<String>,
+}
+
+impl HistoryEntry {
+	pub fn matches(&self, filter: &str) -> bool {
+		let filter = filter.trim().to_lowercase();
+		filter.is_empty()
+			|| [self.title.as_ref(), self.uploader.as_ref(), Some(&self.exit_status)]
+				.into_iter()
+				.flatten()
+				.chain(&self.urls)
+				.chain(&self.output_files)
+				.any(|field| field.to_lowercase().contains(&filter))
+	}
+
+	pub fn folder(&self) -> Option<PathBuf> {
+		let base = self.output_dir.as_ref().map(PathBuf::from).unwrap_or_default();
+		match self.output_files.first() {
+			Some(file) => base.join(file).parent().map(Path::to_path_buf),
+			None => self.output_dir.as_ref().map(PathBuf::from),
+		}
+		.filter(|p| !p.as_os_str().is_empty())
+	}
+}
+
+// Entries written before batches were stored as a list hold a single space-separated url string.
+fn url_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where D: Deserializer<'de> {
+	#[derive(Deserialize)]
+	#[serde(untagged)]
+	enum Urls {
+		List(Vec<String>),
+		Joined(String),
+	}
+	Ok(match Urls::deserialize(deserializer)? {
+		Urls::List(urls) => urls,
+		Urls::Joined(url) => url.split_whitespace().map(String::from).collect(),
+	})
+}
+
+#[derive(Clone)]
+pub struct HistoryStore {
+	path: PathBuf,
+	entries: Arc<Mutex<Vec<HistoryEntry>>>,
+	revision: Arc<AtomicU64>,
+}
+
+impl HistoryStore {
+	pub fn load(path: PathBuf) -> Self {
+		let entries = fs::read_to_string(&path)
+			.map(|s| s.lines().filter_map(|l| serde_json::from_str(l).ok()).collect())
+			.unwrap_or_default();
+		Self { path, entries: Arc::new(Mutex::new(entries)), revision: Arc::new(AtomicU64::new(0)) }
+	}
+
+	pub fn revision(&self) -> u64 { self.revision.load(Ordering::Relaxed) }
+
+	pub fn entries(&self) -> Vec<HistoryEntry> { self.entries.lock().expect("History entries lock failed").clone() }
+
+	pub fn record_job(&self, job: &Job, exit_status: &str) {
+		let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+		let base = HistoryEntry {
+			urls: job.urls.clone(),
+			title: None,
+			uploader: None,
+			format_selector: job.format_selector.clone(),
+			output_files: job.output_files.clone(),
+			size_bytes: None,
+			duration_secs: None,
+			finished_at,
+			exit_status: exit_status.to_string(),
+			output_dir: job.output_dir.clone(),
+			options: job.options.clone(),
+			custom_command: job.custom_command.clone(),
+		};
+		let mut new_entries: Vec<HistoryEntry> = job
+			.info_path
+			.as_ref()
+			.and_then(|p| fs::read_to_string(p).ok())
+			.map(|s| {
+				s.lines()
+					.filter_map(|l| serde_json::from_str::<Value>(l).ok())
+					.map(|info| {
+						let text = |key: &str| info.get(key).and_then(Value::as_str).map(String::from);
+						HistoryEntry {
+							urls: text("webpage_url").map(|u| vec![u]).unwrap_or_else(|| base.urls.clone()),
+							title: text("title"),
+							uploader: text("uploader"),
+							format_selector: base.format_selector.clone().or_else(|| text("format_id")),
+							output_files: text("filepath").into_iter().collect(),
+							size_bytes: ["filesize", "filesize_approx"]
+								.iter()
+								.find_map(|k| info.get(*k).and_then(Value::as_f64))
+								.map(|v| v as u64),
+							duration_secs: info.get("duration").and_then(Value::as_f64),
+							options: base.options.clone().map(single_video_options),
+							..base.clone()
+						}
+					})
+					.collect()
+			})
+			.unwrap_or_default();
+		let mut seen = HashSet::new();
+		new_entries.retain(|e| seen.insert(e.urls.clone()));
+		if new_entries.is_empty() {
+			new_entries.push(base);
+		}
+
+		if let Some(dir) = self.path.parent() {
+			let _ = fs::create_dir_all(dir);
+		}
+		if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&self.path) {
+			for entry in &new_entries {
+				if let Ok(line) = serde_json::to_string(entry) {
+					let _ = writeln!(file, "{}", line);
+				}
+			}
+		}
+		self.entries.lock().expect("History entries lock failed").extend(new_entries);
+		self.revision.fetch_add(1, Ordering::Relaxed);
+	}
+}
+
+fn single_video_options(mut options: DownloadOptions) -> DownloadOptions {
+	if let DownloadMode::Playlist { video_format, audio_formats, .. } = options.mode {
+		options.mode = DownloadMode::Single { video_format, audio_formats };
+	}
+	options
+}
+
+pub fn format_timestamp(secs: u64) -> String {
+	let days = (secs / 86_400) as i64;
+	let (hour, minute) = ((secs % 86_400) / 3600, (secs % 3600) / 60);
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let doe = z - era * 146_097;
+	let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+	let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+	let mp = (5 * doy + 2) / 153;
+	let day = doy - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = yoe + era * 400 + i64::from(month <= 2);
+	format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, hour, minute)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::download_manager::{DownloadManager, JobState};
+
+	fn test_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("tubex-history-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).expect("Create test directory");
+		dir
+	}
+
+	fn entry(urls: &[&str], output_dir: Option<&str>, output_files: &[&str]) -> HistoryEntry {
+		HistoryEntry {
+			urls: urls.iter().map(|u| u.to_string()).collect(),
+			title: Some("Some Video".into()),
+			uploader: Some("Uploader".into()),
+			format_selector: None,
+			output_files: output_files.iter().map(|f| f.to_string()).collect(),
+			size_bytes: None,
+			duration_secs: None,
+			finished_at: 0,
+			exit_status: "Finished".into(),
+			output_dir: output_dir.map(String::from),
+			options: None,
+			custom_command: None,
+		}
+	}
+
+	#[test]
+	fn recorded_jobs_round_trip_through_the_file() {
+		let dir = test_dir("round-trip");
+		let dm = DownloadManager::new(dir.join("Tubex.queue.json"), dir.join("logs"));
+		let urls = vec!["https://example.com/a".to_string(), "https://example.com/b".to_string()];
+		let id = dm.create_job(
+			urls.clone(),
+			None,
+			Some("yt-dlp {url}".into()),
+			Some("/downloads".into()),
+			JobState::Running,
+		);
+		let path = dir.join("history.jsonl");
+
+		let store = HistoryStore::load(path.clone());
+		store.record_job(&dm.get_job(id).unwrap(), "Finished");
+		assert_eq!(store.revision(), 1);
+
+		let entries = HistoryStore::load(path).entries();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].urls, urls);
+		assert_eq!(entries[0].exit_status, "Finished");
+		assert_eq!(entries[0].output_dir.as_deref(), Some("/downloads"));
+		assert_eq!(entries[0].custom_command.as_deref(), Some("yt-dlp {url}"));
+	}
+
+	#[test]
+	fn skips_malformed_lines_and_reads_joined_urls() {
+		let dir = test_dir("malformed");
+		let path = dir.join("history.jsonl");
+		let good = serde_json::to_string(&entry(&["https://example.com/a"], None, &[])).unwrap();
+		let legacy = good
+			.replace(r#""urls":["https://example.com/a"]"#, r#""url":"https://example.com/b https://example.com/c""#);
+		fs::write(&path, format!("{}\n{{\"urls\": [\n\nnot json\n{}\n", good, legacy)).unwrap();
+
+		let entries = HistoryStore::load(path).entries();
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].urls, ["https://example.com/a"]);
+		assert_eq!(entries[1].urls, ["https://example.com/b", "https://example.com/c"]);
+	}
+
+	#[test]
+	fn formats_timestamps() {
+		assert_eq!(format_timestamp(0), "1970-01-01 00:00");
+		assert_eq!(format_timestamp(951_825_600), "2000-02-29 12:00");
+		assert_eq!(format_timestamp(1_709_210_096), "2024-02-29 12:34");
+		assert_eq!(format_timestamp(1_735_689_599), "2024-12-31 23:59");
+	}
+
+	#[test]
+	fn matches_any_field_ignoring_case() {
+		let e = entry(&["https://example.com/watch?v=abc"], None, &["Clip.mp4"]);
+		assert!(e.matches(""));
+		assert!(e.matches("  "));
+		assert!(e.matches("some video"));
+		assert!(e.matches("UPLOADER"));
+		assert!(e.matches("v=abc"));
+		assert!(e.matches("clip.mp4"));
+		assert!(e.matches("finished"));
+		assert!(!e.matches("cancelled"));
+	}
+
+	#[test]
+	fn folder_follows_the_first_output_file() {
+		assert_eq!(
+			entry(&[], Some("/downloads"), &["Playlist/Clip.mp4"]).folder(),
+			Some(PathBuf::from("/downloads/Playlist"))
+		);
+		assert_eq!(entry(&[], None, &["/videos/Clip.mp4"]).folder(), Some(PathBuf::from("/videos")));
+		assert_eq!(entry(&[], Some("/downloads"), &[]).folder(), Some(PathBuf::from("/downloads")));
+		assert_eq!(entry(&[], None, &["Clip.mp4"]).folder(), None);
+		assert_eq!(entry(&[], None, &[]).folder(), None);
+	}
+}
@@ -0,0 +1,125 @@
+use std::sync::{Arc, Mutex, mpsc};
+
+use wxdragon::{
+	ListColumnFormat, ListCtrlStyle, Orientation, PanelStyle, clipboard,
+	prelude::*,
+	widgets::{ListCtrl, Panel},
+};
+
+use crate::{
+	events::AppEvent,
+	history::{self, HistoryEntry, HistoryStore},
+	platform, progress,
+};
+
+fn fill_list(list: &ListCtrl, store: &HistoryStore, filter: &str) -> Vec<HistoryEntry> {
+	let mut entries: Vec<HistoryEntry> = store.entries().into_iter().filter(|e| e.matches(filter)).collect();
+	entries.reverse();
+	list.delete_all_items();
+	for (i, entry) in entries.iter().enumerate() {
+		let row = i as i64;
+		list.insert_item(row, &history::format_timestamp(entry.finished_at), None);
+		list.set_item_text_by_column(row, 1, entry.title.as_deref().unwrap_or_default());
+		list.set_item_text_by_column(row, 2, entry.uploader.as_deref().unwrap_or_default());
+		list.set_item_text_by_column(row, 3, &entry.exit_status);
+		list.set_item_text_by_column(
+			row,
+			4,
+			&entry.size_bytes.map(|b| progress::format_bytes(b as f64)).unwrap_or_default(),
+		);
+		list.set_item_text_by_column(row, 5, &entry.urls.join(" "));
+	}
+	entries
+}
+
+pub fn create_history_tab(parent: &impl WxWidget, store: HistoryStore, tx_app: mpsc::Sender<AppEvent>) -> Panel {
+	let panel = Panel::builder(parent).with_style(PanelStyle::TabTraversal).build();
+	let sizer = BoxSizer::builder(Orientation::Vertical).build();
+
+	let top_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let filter_label = StaticText::builder(&panel).with_label("Filter:").build();
+	let filter_text = TextCtrl::builder(&panel).build();
+	top_sizer.add(&filter_label, 0, SizerFlag::All | SizerFlag::AlignCenterVertical, 5);
+	top_sizer.add(&filter_text, 1, SizerFlag::All | SizerFlag::Expand, 5);
+	sizer.add_sizer(&top_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let list = ListCtrl::builder(&panel).with_style(ListCtrlStyle::Report | ListCtrlStyle::SingleSel).build();
+	list.insert_column(0, "Finished (UTC)", ListColumnFormat::Left, 130);
+	list.insert_column(1, "Title", ListColumnFormat::Left, 260);
+	list.insert_column(2, "Uploader", ListColumnFormat::Left, 130);
+	list.insert_column(3, "Status", ListColumnFormat::Left, 110);
+	list.insert_column(4, "Size", ListColumnFormat::Left, 80);
+	list.insert_column(5, "URL", ListColumnFormat::Left, 300);
+	sizer.add(&list, 1, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let bottom_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let open_folder_btn = Button::builder(&panel).with_label("Open Folder").build();
+	let redownload_btn = Button::builder(&panel).with_label("Re-download").build();
+	let copy_url_btn = Button::builder(&panel).with_label("Copy URL").build();
+	let buttons = [open_folder_btn, redownload_btn, copy_url_btn];
+	for btn in &buttons {
+		btn.enable(false);
+		bottom_sizer.add(btn, 0, SizerFlag::All, 5);
+	}
+	sizer.add_sizer(&bottom_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+	panel.set_sizer(sizer, true);
+
+	let shown = Arc::new(Mutex::new(fill_list(&list, &store, "")));
+	let selected_entry = {
+		let shown = shown.clone();
+		move || {
+			let idx = list.get_first_selected_item();
+			(idx >= 0).then(|| shown.lock().ok().and_then(|s| s.get(idx as usize).cloned())).flatten()
+		}
+	};
+
+	let store_filter = store.clone();
+	let shown_filter = shown.clone();
+	filter_text.on_text_changed(move |_| {
+		*shown_filter.lock().expect("History view lock failed") =
+			fill_list(&list, &store_filter, &filter_text.get_value());
+		buttons.iter().for_each(|b| b.enable(false));
+	});
+
+	list.on_item_selected(move |_| buttons.iter().for_each(|b| b.enable(true)));
+	list.on_item_deselected(move |_| buttons.iter().for_each(|b| b.enable(false)));
+
+	let sel_open = selected_entry.clone();
+	open_folder_btn.on_click(move |_| {
+		if let Some(dir) = sel_open().and_then(|e| e.folder())
+			&& let Err(e) = platform::open_path(&dir)
+		{
+			let _ = MessageDialog::builder(&panel, &format!("Could not open {}: {}", dir.display(), e), "Error")
+				.build()
+				.show_modal();
+		}
+	});
+
+	let sel_redownload = selected_entry.clone();
+	redownload_btn.on_click(move |_| {
+		if let Some(entry) = sel_redownload() {
+			let _ = tx_app.send(AppEvent::Redownload(Box::new(entry)));
+		}
+	});
+
+	copy_url_btn.on_click(move |_| {
+		if let Some(entry) = selected_entry() {
+			clipboard::Clipboard::get().set_text(&entry.urls.join(" "));
+		}
+	});
+
+	let timer = Box::leak(Box::new(Timer::new(&panel)));
+	let last_revision = Mutex::new(store.revision());
+	timer.on_tick(move |_| {
+		let revision = store.revision();
+		let mut last = last_revision.lock().expect("History revision lock failed");
+		if *last != revision {
+			*last = revision;
+			*shown.lock().expect("History view lock failed") = fill_list(&list, &store, &filter_text.get_value());
+			buttons.iter().for_each(|b| b.enable(false));
+		}
+	});
+	timer.start(1000, false);
+
+	panel
+}
@@ -0,0 +1,110 @@
+use std::{
+	collections::VecDeque,
+	fs::{self, File, OpenOptions},
+	io::Write,
+	path::{Path, PathBuf},
+};
+
+const MAX_BUFFERED_BYTES: usize = 32 * 1024;
+
+pub struct JobLog {
+	path: PathBuf,
+	file: Option<File>,
+	lines: VecDeque<String>,
+	buffered_bytes: usize,
+	dropped_lines: usize,
+}
+
+impl JobLog {
+	pub fn new(path: PathBuf) -> Self {
+		let file = path
+			.parent()
+			.and_then(|dir| fs::create_dir_all(dir).ok())
+			.and_then(|_| OpenOptions::new().create(true).append(true).open(&path).ok());
+		Self { path, file, lines: VecDeque::new(), buffered_bytes: 0, dropped_lines: 0 }
+	}
+
+	pub fn path(&self) -> &Path { &self.path }
+
+	pub fn append(&mut self, text: &str) {
+		for line in text.lines() {
+			if let Some(file) = &mut self.file {
+				let _ = writeln!(file, "{}", line);
+			}
+			self.buffered_bytes += line.len() + 1;
+			self.lines.push_back(line.to_string());
+		}
+		while self.buffered_bytes > MAX_BUFFERED_BYTES
+			&& let Some(old) = self.lines.pop_front()
+		{
+			self.buffered_bytes -= old.len() + 1;
+			self.dropped_lines += 1;
+		}
+	}
+
+	pub fn tail(&self) -> String {
+		let mut out = String::with_capacity(self.buffered_bytes + 80);
+		if self.dropped_lines > 0 {
+			out.push_str(&format!("[... {} earlier lines are in the full log ...]\n", self.dropped_lines));
+		}
+		for line in &self.lines {
+			out.push_str(line);
+			out.push('\n');
+		}
+		out
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn log_path(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("tubex-job-log-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		dir.join("logs").join("job.log")
+	}
+
+	#[test]
+	fn keeps_short_output_in_memory_and_on_disk() {
+		let path = log_path("short");
+		let mut log = JobLog::new(path.clone());
+		log.append("[youtube] abc: Downloading webpage\n[download] Destination: Clip.mp4");
+		log.append("[download] 100%");
+		let expected = "[youtube] abc: Downloading webpage\n[download] Destination: Clip.mp4\n[download] 100%\n";
+		assert_eq!(log.tail(), expected);
+		assert_eq!(log.path(), path);
+		assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+	}
+
+	#[test]
+	fn caps_the_buffer_and_spools_everything_to_disk() {
+		let path = log_path("cap");
+		let mut log = JobLog::new(path.clone());
+		let lines: Vec<String> = (0..1000).map(|i| format!("[download] line {:04} {}", i, "x".repeat(80))).collect();
+		for line in &lines {
+			log.append(line);
+		}
+		let line_bytes = lines[0].len() + 1;
+		let kept = MAX_BUFFERED_BYTES / line_bytes;
+		assert!(log.buffered_bytes <= MAX_BUFFERED_BYTES);
+		assert_eq!(log.lines.len(), kept);
+		assert_eq!(log.dropped_lines, lines.len() - kept);
+
+		let tail = log.tail();
+		let mut tail_lines = tail.lines();
+		assert_eq!(
+			tail_lines.next(),
+			Some(format!("[... {} earlier lines are in the full log ...]", lines.len() - kept).as_str())
+		);
+		assert!(tail_lines.eq(lines[lines.len() - kept..].iter().map(String::as_str)));
+		assert_eq!(fs::read_to_string(&path).unwrap(), lines.join("\n") + "\n");
+	}
+
+	#[test]
+	fn a_single_oversized_line_is_not_kept() {
+		let mut log = JobLog::new(log_path("oversized"));
+		log.append(&"x".repeat(MAX_BUFFERED_BYTES + 1));
+		assert_eq!(log.tail(), "[... 1 earlier lines are in the full log ...]\n");
+	}
+}
@@ -3,41 +3,78 @@
 
 mod config;
 mod config_dialog;
+mod destination;
+mod download_archive;
 mod download_manager;
+mod download_options;
 mod events;
+mod format_selector;
+mod history;
+mod history_tab;
+mod job_log;
 mod options_dialog;
+mod output_template;
+mod platform;
+mod presets;
+mod progress;
+mod retry;
 mod search_tab;
 mod settings_tab;
 mod startup;
+mod version_manager;
 mod video_info;
 
 use std::{
 	io::{BufRead, BufReader},
-	os::windows::process::CommandExt,
-	process::{Command, Stdio},
+	process::Stdio,
 	sync::{Arc, Mutex, mpsc},
 	thread,
+	time::{Duration, Instant},
 };
 
 use config_dialog::show_config_dialog;
-use download_manager::DownloadManager;
+use download_manager::{DownloadManager, JobId, JobState};
+use download_options::{DownloadMode, DownloadOptions};
 use events::AppEvent;
-use options_dialog::{DownloadMode, DownloadOptions, show_options_dialog, show_playlist_dialog};
+use history::HistoryStore;
+use history_tab::create_history_tab;
+use options_dialog::{show_options_dialog, show_playlist_dialog};
+use output_template::TemplateKind;
+use platform::expand_env_vars;
+use progress::ProgressInfo;
+use retry::ErrorClass;
 use search_tab::create_search_tab;
 use settings_tab::create_settings_tab;
+use version_manager::VersionManager;
 use video_info::VideoInfo;
 use wxdragon::{
-	PanelStyle, TextCtrlStyle, clipboard,
+	ListColumnFormat, ListCtrlStyle, ListItemState, PanelStyle, TextCtrlStyle, clipboard,
 	prelude::*,
-	widgets::{Choice, Gauge, ListBox, Notebook},
+	widgets::{Choice, Gauge, ListCtrl, Notebook},
 };
 
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+const QUEUE_FILENAME: &str = "Tubex.queue.json";
+const HISTORY_FILENAME: &str = "Tubex.history.jsonl";
+const LOG_DIRECTORY: &str = "logs";
+const LOG_WILDCARD: &str = "Log Files (*.log)|*.log|All Files (*.*)|*.*";
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const ARCHIVE_WILDCARD: &str =
+	"Archives (*.zip;*.tar;*.tar.gz;*.tgz;*.tar.xz)|*.zip;*.tar;*.tar.gz;*.tgz;*.tar.xz|All Files (*.*)|*.*";
 
 fn main() {
 	let _ = wxdragon::main(|_| {
 		let config_manager = Arc::new(Mutex::new(config::ConfigManager::new()));
-		let download_manager = Arc::new(DownloadManager::new());
+		let (download_manager, history_store) = {
+			let cfg = config_manager.lock().expect("Config manager lock failed");
+			(
+				Arc::new(DownloadManager::new(cfg.data_file(QUEUE_FILENAME), cfg.data_file(LOG_DIRECTORY))),
+				HistoryStore::load(cfg.data_file(HISTORY_FILENAME)),
+			)
+		};
+		download_manager.set_max_concurrent(
+			config_manager.lock().expect("Config manager lock failed").get_max_concurrent_downloads(),
+		);
+		let version_manager = VersionManager::new();
 
 		let frame = Frame::builder().with_title("Tubex Downloader").with_size(Size::new(900, 750)).build();
 		let notebook = Notebook::builder(&frame).build();
@@ -72,7 +109,15 @@ fn main() {
 			}
 		});
 
-		let settings_panel = create_settings_tab(&notebook, config_manager.clone());
+		let history_panel = create_history_tab(&notebook, history_store.clone(), tx.clone());
+		notebook.add_page(&history_panel, "History", false, None);
+
+		let settings_panel = create_settings_tab(
+			&notebook,
+			config_manager.clone(),
+			version_manager.clone(),
+			Arc::clone(&download_manager),
+		);
 		notebook.add_page(&settings_panel, "Settings", false, None);
 
 		let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
@@ -108,15 +153,43 @@ fn main() {
 			StaticText::builder(&downloader_panel).with_label("Downloads Status (Select to view output)").build();
 		main_sizer.add(&list_label, 0, SizerFlag::All, 5);
 
-		let status_list = ListBox::builder(&downloader_panel).build();
+		let status_list =
+			ListCtrl::builder(&downloader_panel).with_style(ListCtrlStyle::Report | ListCtrlStyle::SingleSel).build();
+		status_list.insert_column(0, "Download", ListColumnFormat::Left, 480);
+		status_list.insert_column(1, "Progress", ListColumnFormat::Left, 360);
 		main_sizer.add(&status_list, 1, SizerFlag::Expand | SizerFlag::All, 5);
 
+		let queue_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+		let queue_toggle_button = Button::builder(&downloader_panel).with_label("Stop Queue").build();
+		let move_up_button = Button::builder(&downloader_panel).with_label("Move Up").build();
+		let move_down_button = Button::builder(&downloader_panel).with_label("Move Down").build();
+		let prioritize_button = Button::builder(&downloader_panel).with_label("Prioritize").build();
+		let pause_all_button = Button::builder(&downloader_panel).with_label("Pause All").build();
+		let resume_all_button = Button::builder(&downloader_panel).with_label("Resume All").build();
+		let pause_button = Button::builder(&downloader_panel).with_label("Pause").build();
+		pause_button.enable(false);
 		let cancel_button = Button::builder(&downloader_panel).with_label("Cancel Selected Download").build();
 		cancel_button.enable(false);
-		main_sizer.add(&cancel_button, 0, SizerFlag::All | SizerFlag::AlignRight, 5);
-
+		queue_sizer.add(&queue_toggle_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&move_up_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&move_down_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&prioritize_button, 0, SizerFlag::All, 5);
+		queue_sizer.add_stretch_spacer(1);
+		queue_sizer.add(&pause_all_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&resume_all_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&pause_button, 0, SizerFlag::All, 5);
+		queue_sizer.add(&cancel_button, 0, SizerFlag::All, 5);
+		main_sizer.add_sizer(&queue_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+		let log_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 		let log_label = StaticText::builder(&downloader_panel).with_label("Command Output").build();
-		main_sizer.add(&log_label, 0, SizerFlag::All, 5);
+		let open_log_button = Button::builder(&downloader_panel).with_label("Open Full Log").build();
+		let export_log_button = Button::builder(&downloader_panel).with_label("Export Log...").build();
+		log_sizer.add(&log_label, 0, SizerFlag::AlignCenterVertical | SizerFlag::All, 5);
+		log_sizer.add_stretch_spacer(1);
+		log_sizer.add(&open_log_button, 0, SizerFlag::All, 5);
+		log_sizer.add(&export_log_button, 0, SizerFlag::All, 5);
+		main_sizer.add_sizer(&log_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
 
 		let output_text =
 			TextCtrl::builder(&downloader_panel).with_style(TextCtrlStyle::MultiLine | TextCtrlStyle::ReadOnly).build();
@@ -131,20 +204,18 @@ fn main() {
 		let tx_clone_timer = tx.clone();
 		let dm_timer = Arc::clone(&download_manager);
 		let cfg_timer = Arc::clone(&config_manager);
+		let history_timer = history_store.clone();
 		let cancel_btn_timer = cancel_button;
+		let pause_btn_timer = pause_button;
 
-		let download_list_ids = Arc::new(Mutex::new(Vec::<String>::new()));
+		let download_list_ids = Arc::new(Mutex::new(Vec::<JobId>::new()));
 		let ids_timer = Arc::clone(&download_list_ids);
 		let ids_sel = Arc::clone(&download_list_ids);
 		let ids_cancel = Arc::clone(&download_list_ids);
-		let ids_get_tag = Arc::clone(&download_list_ids);
+		let ids_get_id = Arc::clone(&download_list_ids);
 
-		let list_tag_lookup = list_clone_timer;
-		let get_selected_tag = move || {
-			list_tag_lookup
-				.get_selection()
-				.and_then(|idx| ids_get_tag.lock().ok().and_then(|ids| ids.get(idx as usize).cloned()))
-		};
+		let list_id_lookup = list_clone_timer;
+		let get_selected_id = move || selected_job(&list_id_lookup, &ids_get_id);
 
 		let download_dialog = std::rc::Rc::new(std::cell::RefCell::new(None));
 		let dd_clone = download_dialog.clone();
@@ -153,17 +224,36 @@ fn main() {
 		let last_clipboard = std::rc::Rc::new(std::cell::RefCell::new(String::new()));
 
 		let _ = tx.send(AppEvent::StartupCheck);
+		let _ = tx.send(AppEvent::OfferResume);
 		timer.start(100, false);
 
 		timer.on_tick(move |_| {
-			let update_item = |tag: &str, new_text: &str| {
+			let update_item = |id: JobId, new_text: &str| {
 				let mut ids = ids_timer.lock().expect("IDs lock failed");
-				if let Some(pos) = ids.iter().position(|t| t == tag) {
+				if let Some(pos) = ids.iter().position(|t| *t == id) {
 					ids.remove(pos);
-					list_clone_timer.delete(pos as u32);
+					list_clone_timer.delete_item(pos as i64);
+				}
+				ids.push(id);
+				list_clone_timer.insert_item(list_clone_timer.get_item_count() as i64, new_text, None);
+			};
+			let set_row_text = |id: JobId, text: &str| {
+				if let Some(pos) = ids_timer.lock().expect("IDs lock failed").iter().position(|t| *t == id) {
+					list_clone_timer.set_item_text_by_column(pos as i64, 0, text);
+				}
+			};
+			let refresh_queue_positions = || {
+				let ids = ids_timer.lock().expect("IDs lock failed");
+				let queued = dm_timer.queued_jobs();
+				for (n, id) in queued.iter().enumerate() {
+					if let Some(pos) = ids.iter().position(|t| t == id) {
+						list_clone_timer.set_item_text_by_column(
+							pos as i64,
+							1,
+							&format!("Position {} of {} in queue", n + 1, queued.len()),
+						);
+					}
 				}
-				ids.push(tag.to_string());
-				list_clone_timer.append(new_text);
 			};
 
 			if let Some(text) = clipboard::Clipboard::get().get_text() {
@@ -196,29 +286,99 @@ fn main() {
 					AppEvent::RequestFetch(url) => {
 						fetch_info(url, tx_clone_timer.clone(), Arc::clone(&cfg_timer), Arc::clone(&dm_timer));
 					}
+					AppEvent::Redownload(entry) => {
+						queue_download(
+							entry.urls,
+							entry.custom_command,
+							entry.options,
+							entry.output_dir,
+							tx_clone_timer.clone(),
+							Arc::clone(&dm_timer),
+							Arc::clone(&cfg_timer),
+						);
+					}
 					AppEvent::StartupCheck => {
 						let c = Arc::clone(&cfg_timer);
 						let t = tx_clone_timer.clone();
+						let vm = version_manager.clone();
 						thread::spawn(move || {
 							let (yt, ff) = startup::check_dependencies(&c);
 							let _ = t.send(AppEvent::StartupResult(yt, ff));
-							startup::update_ytdlp(&c);
+							let (yt_path, auto_update) = {
+								let c = c.lock().expect("Config manager lock failed");
+								(c.get_yt_dlp_path(), c.get_auto_update())
+							};
+							if yt && auto_update && VersionManager::is_app_managed(&yt_path) {
+								let _ = vm.update(&c);
+							}
 						});
 					}
+					AppEvent::OfferResume => {
+						let saved = dm_timer.load_saved_jobs();
+						if saved.is_empty() {
+							continue;
+						}
+						let msg = format!(
+							"{} unfinished download(s) from the previous session were found.\n\nResume them now?",
+							saved.len()
+						);
+						let answer = MessageDialog::builder(&frame_clone_timer, &msg, "Resume Downloads")
+							.with_style(MessageDialogStyle::YesNo | MessageDialogStyle::IconQuestion)
+							.build()
+							.show_modal();
+						if answer != wxdragon::id::ID_YES {
+							dm_timer.discard_saved_jobs();
+							continue;
+						}
+						for job in saved {
+							let id = dm_timer.restore_job(job);
+							if let Some(job) = dm_timer.get_job(id) {
+								let prefix = if job.state == JobState::Paused { "Paused" } else { "Queued" };
+								let _ =
+									tx_clone_timer.send(AppEvent::Status(id, format!("{}: {}", prefix, job.label())));
+							}
+						}
+						let _ = tx_clone_timer.send(AppEvent::QueueChanged);
+					}
 					AppEvent::StartupResult(yt_ok, ffmpeg_ok) => {
-						if (!yt_ok || !ffmpeg_ok) && show_setup_dialog(&frame_clone_timer, (yt_ok, ffmpeg_ok)) == 2 {
-							*dd_clone.borrow_mut() = Some(show_download_progress(&frame_clone_timer));
-							let tx_dl = tx_clone_timer.clone();
-							thread::spawn(move || {
-								let exe_dir = std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("."));
-								let target_dir = exe_dir.parent().unwrap_or(std::path::Path::new("."));
-								let _ = tx_dl.send(AppEvent::DownloadComplete(startup::download_tools_script(
-									target_dir,
-									tx_dl.clone(),
-									!yt_ok,
-									!ffmpeg_ok,
-								)));
-							});
+						if yt_ok && ffmpeg_ok {
+							continue;
+						}
+						match show_setup_dialog(&frame_clone_timer, (yt_ok, ffmpeg_ok)) {
+							2 => {
+								*dd_clone.borrow_mut() = Some(show_download_progress(&frame_clone_timer));
+								let tx_dl = tx_clone_timer.clone();
+								let mirrors = cfg_timer.lock().map(|c| c.get_tool_mirrors()).unwrap_or_default();
+								thread::spawn(move || {
+									let _ = tx_dl.send(AppEvent::DownloadComplete(startup::download_tools_script(
+										&startup::tools_dir(),
+										&startup::ToolSources::for_current_platform(&mirrors),
+										tx_dl.clone(),
+										!yt_ok,
+										!ffmpeg_ok,
+									)));
+								});
+							}
+							3 => {
+								let dialog = FileDialog::builder(&frame_clone_timer)
+									.with_message("Select yt-dlp / ffmpeg Archive")
+									.with_wildcard(ARCHIVE_WILDCARD)
+									.build();
+								if dialog.show_modal() == wxdragon::id::ID_OK
+									&& let Some(archive) = dialog.get_path()
+								{
+									*dd_clone.borrow_mut() = Some(show_download_progress(&frame_clone_timer));
+									let tx_dl = tx_clone_timer.clone();
+									thread::spawn(move || {
+										let _ = tx_dl.send(AppEvent::DownloadComplete(startup::install_from_archive(
+											std::path::Path::new(&archive),
+											&startup::tools_dir(),
+											tx_dl.clone(),
+										)));
+									});
+								}
+							}
+							_ => {}
 						}
 					}
 					AppEvent::DownloadComplete(res) => {
@@ -226,19 +386,23 @@ fn main() {
 							d.close(true);
 						}
 						match res {
-							Ok(_) => {
+							Ok(installed) => {
 								if let Ok(mut c) = cfg_timer.lock() {
-									let exe_dir =
-										std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("."));
-									let target_dir = exe_dir.parent().unwrap_or(std::path::Path::new("."));
-									c.set_yt_dlp_path(&target_dir.join("yt-dlp.exe").to_string_lossy());
-									c.set_ffmpeg_path(&target_dir.join("ffmpeg.exe").to_string_lossy());
-									c.set_update_channel("stable");
+									let target_dir = startup::tools_dir();
+									let yt_dlp = platform::exe_name("yt-dlp");
+									let ffmpeg = platform::exe_name("ffmpeg");
+									if installed.contains(&yt_dlp) {
+										c.set_yt_dlp_path(&target_dir.join(&yt_dlp).to_string_lossy());
+										c.set_update_channel("stable");
+									}
+									if installed.contains(&ffmpeg) {
+										c.set_ffmpeg_path(&target_dir.join(&ffmpeg).to_string_lossy());
+									}
 									c.flush();
 								}
 								let _ = MessageDialog::builder(
 									&frame_clone_timer,
-									"Download complete! Tools are ready.",
+									&format!("Installed: {}. Tools are ready.", installed.join(", ")),
 									"Success",
 								)
 								.build()
@@ -247,7 +411,7 @@ fn main() {
 							Err(e) => {
 								let _ = MessageDialog::builder(
 									&frame_clone_timer,
-									&format!("Download failed: {}", e),
+									&format!("Installation failed: {}", e),
 									"Error",
 								)
 								.build()
@@ -255,37 +419,69 @@ fn main() {
 							}
 						}
 					}
-					AppEvent::Status(tag, msg) => {
-						update_item(&tag, &msg);
-						if list_clone_timer.get_count() > 0 {
-							list_clone_timer.set_selection(list_clone_timer.get_count() - 1, true);
-							output_clone_timer.set_value(&dm_timer.get_output(&tag));
+					AppEvent::Status(id, msg) => {
+						update_item(id, &msg);
+						if list_clone_timer.get_item_count() > 0 {
+							let last = (list_clone_timer.get_item_count() - 1) as i64;
+							list_clone_timer.set_item_state(last, ListItemState::Selected, ListItemState::Selected);
+							output_clone_timer.set_value(&dm_timer.get_output(id));
 							cancel_btn_timer.enable(true);
+							update_pause_button(&pause_btn_timer, dm_timer.get_job(id).map(|j| j.state));
 						}
 					}
-					AppEvent::Output(tag, msg) => {
-						dm_timer.append_output(&tag, &msg);
-						if get_selected_tag().is_some_and(|s| s == tag) {
+					AppEvent::Output(id, msg) => {
+						dm_timer.append_output(id, &msg);
+						if get_selected_id().is_some_and(|s| s == id) {
 							pending_log_update.push_str(&msg);
 							pending_log_update.push('\n');
 						}
 					}
-					AppEvent::Finished(url) => {
-						update_item(&url, &format!("Finished: {}", url));
-						if get_selected_tag().is_some_and(|s| s == url) {
+					AppEvent::Progress(id, progress) => {
+						if let Some(pos) = ids_timer.lock().expect("IDs lock failed").iter().position(|t| *t == id) {
+							list_clone_timer.set_item_text_by_column(pos as i64, 1, &progress.summary());
+						}
+					}
+					AppEvent::Finished(id) => {
+						dm_timer.set_state(id, JobState::Finished);
+						if let Some(job) = dm_timer.get_job(id).filter(|j| j.info_path.is_some()) {
+							history_timer.record_job(&job, "Finished");
+						}
+						let label = dm_timer.get_job(id).map(|j| j.label()).unwrap_or_default();
+						update_item(id, &format!("Finished: {}", label));
+						if get_selected_id().is_some_and(|s| s == id) {
 							cancel_btn_timer.enable(false);
+							pause_btn_timer.enable(false);
 							output_clone_timer.append_text("--- Finished ---\n");
 						}
-						dm_timer.unregister_task(&url);
+						dm_timer.unregister_task(id);
 					}
-					AppEvent::Error(tag, err_msg) => {
-						update_item(&tag, &format!("Error: {}", tag));
-						dm_timer.append_output(&tag, &format!("Error: {}", err_msg));
-						if get_selected_tag().is_some_and(|s| s == tag) {
+					AppEvent::Error(id, err_msg) => {
+						let job = dm_timer.get_job(id);
+						if job.as_ref().is_some_and(|j| matches!(j.state, JobState::Paused | JobState::Queued)) {
+							dm_timer.unregister_task(id);
+							if job.is_some_and(|j| j.state == JobState::Paused) {
+								let _ = tx_clone_timer.send(AppEvent::Paused(id));
+							}
+							continue;
+						}
+						let cancelled = job.as_ref().is_some_and(|j| j.state == JobState::Cancelled);
+						if !cancelled {
+							dm_timer.set_state(id, JobState::Failed);
+						}
+						if let Some(j) = job.as_ref().filter(|j| j.info_path.is_some()) {
+							let status =
+								if cancelled { "Cancelled".to_string() } else { format!("Error: {}", err_msg) };
+							history_timer.record_job(j, &status);
+						}
+						let label = job.map(|j| j.label()).unwrap_or_default();
+						update_item(id, &format!("{}: {}", if cancelled { "Cancelled" } else { "Error" }, label));
+						dm_timer.append_output(id, &format!("Error: {}", err_msg));
+						if get_selected_id().is_some_and(|s| s == id) {
 							cancel_btn_timer.enable(false);
+							pause_btn_timer.enable(false);
 							output_clone_timer.append_text(&format!("Error: {}\n", err_msg));
 						}
-						dm_timer.unregister_task(&tag);
+						dm_timer.unregister_task(id);
 					}
 					AppEvent::DownloadProgress(msg, val) => {
 						if let Some((d, g)) = &*dd_clone.borrow() {
@@ -293,28 +489,130 @@ fn main() {
 							g.set_value(val);
 						}
 					}
-					AppEvent::ShowOptions(url, infos) => {
-						if let Some(opts) = if infos.len() > 1 {
-							show_playlist_dialog(&frame_clone_timer, &infos)
+					AppEvent::QueueChanged => refresh_queue_positions(),
+					AppEvent::Paused(id) => {
+						let label = dm_timer.get_job(id).map(|j| j.label()).unwrap_or_default();
+						set_row_text(id, &format!("Paused: {}", label));
+						dm_timer.append_output(id, "--- Paused ---");
+						if get_selected_id().is_some_and(|s| s == id) {
+							output_clone_timer.append_text("--- Paused ---\n");
+							update_pause_button(&pause_btn_timer, Some(JobState::Paused));
+						}
+					}
+					AppEvent::Retrying(id, msg) => {
+						if let Some(pos) = ids_timer.lock().expect("IDs lock failed").iter().position(|t| *t == id) {
+							list_clone_timer.set_item_text_by_column(pos as i64, 1, &msg);
+						}
+					}
+					AppEvent::Resumed(id) => {
+						let label = dm_timer.get_job(id).map(|j| j.label()).unwrap_or_default();
+						set_row_text(id, &format!("Queued: {}", label));
+						refresh_queue_positions();
+						if get_selected_id().is_some_and(|s| s == id) {
+							update_pause_button(&pause_btn_timer, Some(JobState::Queued));
+						}
+					}
+					AppEvent::ShowOptions(_, url, infos) => {
+						let (default_preset, sponsorblock, metadata, archive, playlist_destination) =
+							match cfg_timer.lock() {
+								Ok(c) => {
+									let download_path = c.get_download_path();
+									let playlist_destination = c
+										.get_playlist_subfolder()
+										.then(|| {
+											destination::playlist_folder(
+												download_path.as_deref().unwrap_or_default(),
+												&infos,
+											)
+										})
+										.flatten();
+									(
+										c.get_default_preset(),
+										Some(c.get_sponsorblock()).filter(|sb| !sb.is_empty()),
+										Some(c.get_metadata_options()).filter(|m| !m.is_empty()),
+										c.archive_path(playlist_destination.as_deref().or(download_path.as_deref()))
+											.map(|p| download_archive::load(&p))
+											.unwrap_or_default(),
+										playlist_destination,
+									)
+								}
+								Err(_) => Default::default(),
+							};
+						let opts = if infos.len() > 1 {
+							match default_preset {
+								Some(preset) => {
+									let indices: Vec<usize> = (1..=infos.len())
+										.filter(|i| !download_archive::contains(&archive, &infos[i - 1]))
+										.collect();
+									(!indices.is_empty()).then(|| DownloadOptions {
+										sponsorblock,
+										metadata,
+										destination: playlist_destination,
+										..DownloadOptions::from_preset(
+											preset,
+											DownloadMode::Playlist {
+												indices,
+												video_format: None,
+												audio_formats: Vec::new(),
+											},
+										)
+									})
+								}
+								None => show_playlist_dialog(&frame_clone_timer, &infos, &cfg_timer),
+							}
 						} else {
-							infos.first().and_then(|i| show_options_dialog(&frame_clone_timer, i))
-						} {
-							start_batch_download(
+							match default_preset {
+								Some(preset) => Some(DownloadOptions {
+									sponsorblock,
+									metadata,
+									..DownloadOptions::from_preset(
+										preset,
+										DownloadMode::Single { video_format: None, audio_formats: Vec::new() },
+									)
+								}),
+								None => {
+									infos.first().and_then(|i| show_options_dialog(&frame_clone_timer, i, &cfg_timer))
+								}
+							}
+						};
+						if let Some(opts) = opts {
+							queue_download(
 								vec![url],
 								None,
 								Some(opts),
+								None,
 								tx_clone_timer.clone(),
 								Arc::clone(&dm_timer),
 								Arc::clone(&cfg_timer),
 							);
 						}
 					}
-					AppEvent::ShowOptionsForMultipleUrls(urls, first_video_info) => {
-						if let Some(opts) = show_options_dialog(&frame_clone_timer, &first_video_info) {
-							start_batch_download(
+					AppEvent::ShowOptionsForMultipleUrls(_, urls, first_video_info) => {
+						let (default_preset, sponsorblock, metadata) = match cfg_timer.lock() {
+							Ok(c) => (
+								c.get_default_preset(),
+								Some(c.get_sponsorblock()).filter(|sb| !sb.is_empty()),
+								Some(c.get_metadata_options()).filter(|m| !m.is_empty()),
+							),
+							Err(_) => Default::default(),
+						};
+						let opts = match default_preset {
+							Some(preset) => Some(DownloadOptions {
+								sponsorblock,
+								metadata,
+								..DownloadOptions::from_preset(
+									preset,
+									DownloadMode::Single { video_format: None, audio_formats: Vec::new() },
+								)
+							}),
+							None => show_options_dialog(&frame_clone_timer, &first_video_info, &cfg_timer),
+						};
+						if let Some(opts) = opts {
+							queue_download(
 								urls,
 								None,
-								Some(opts.clone()),
+								Some(opts),
+								None,
 								tx_clone_timer.clone(),
 								Arc::clone(&dm_timer),
 								Arc::clone(&cfg_timer),
@@ -324,10 +622,18 @@ fn main() {
 				}
 			}
 
+			let ready = dm_timer.take_ready_jobs();
+			if !ready.is_empty() {
+				for id in ready {
+					start_queued_download(id, tx_clone_timer.clone(), Arc::clone(&dm_timer), Arc::clone(&cfg_timer));
+				}
+				refresh_queue_positions();
+			}
+
 			if !pending_log_update.is_empty() {
 				if output_clone_timer.get_last_position() > 60_000 {
-					if let Some(tag) = get_selected_tag() {
-						output_clone_timer.set_value(&dm_timer.get_output(&tag));
+					if let Some(id) = get_selected_id() {
+						output_clone_timer.set_value(&dm_timer.get_output(id));
 					}
 				} else {
 					output_clone_timer.append_text(&pending_log_update);
@@ -347,36 +653,181 @@ fn main() {
 			choice_clone.set_selection(0);
 		});
 
-		let status_list_sel = status_list;
 		let output_text_sel = output_text;
 		let dm_sel = Arc::clone(&download_manager);
 		let cancel_btn_sel = cancel_button;
-		status_list.on_selection_changed(move |event| {
-			if let Some(idx) = event.get_selection() {
-				if let Some(ids) = ids_sel.lock().ok()
-					&& let Some(tag) = ids.get(idx as usize)
-					&& let Some(s) = status_list_sel.get_string(idx as u32)
-				{
-					cancel_btn_sel.enable(s.starts_with("Started:") || s.starts_with("Fetching info:"));
-					output_text_sel.set_value(&dm_sel.get_output(tag));
+		let pause_btn_sel = pause_button;
+		status_list.on_item_selected(move |event| {
+			let idx = event.get_item_index();
+			if idx >= 0
+				&& let Some(ids) = ids_sel.lock().ok()
+				&& let Some(&id) = ids.get(idx as usize)
+			{
+				let state = dm_sel.get_job(id).map(|j| j.state);
+				cancel_btn_sel.enable(state.is_some_and(|s| s.is_active() || s == JobState::Paused));
+				update_pause_button(&pause_btn_sel, state);
+				output_text_sel.set_value(&dm_sel.get_output(id));
+			}
+		});
+		let cancel_btn_desel = cancel_button;
+		let pause_btn_desel = pause_button;
+		status_list.on_item_deselected(move |_| {
+			cancel_btn_desel.enable(false);
+			pause_btn_desel.enable(false);
+		});
+
+		let list_pause = status_list;
+		let ids_pause = Arc::clone(&download_list_ids);
+		let dm_pause = Arc::clone(&download_manager);
+		let tx_pause = tx.clone();
+		let pause_btn = pause_button;
+		pause_button.on_click(move |_| {
+			let Some(id) = selected_job(&list_pause, &ids_pause) else {
+				return;
+			};
+			match dm_pause.get_job(id).map(|j| j.state) {
+				Some(JobState::Paused) => {
+					if dm_pause.resume_task(id) {
+						let _ = tx_pause.send(AppEvent::Resumed(id));
+					}
 				}
-			} else {
-				cancel_btn_sel.enable(false);
+				Some(JobState::Queued) => {
+					if dm_pause.pause_task(id) {
+						list_pause.set_item_text_by_column(list_pause.get_first_selected_item() as i64, 1, "");
+						let _ = tx_pause.send(AppEvent::Paused(id));
+						let _ = tx_pause.send(AppEvent::QueueChanged);
+					}
+				}
+				_ => {
+					if dm_pause.pause_task(id) {
+						pause_btn.enable(false);
+					}
+				}
+			}
+		});
+
+		let list_pause_all = status_list;
+		let ids_pause_all = Arc::clone(&download_list_ids);
+		let dm_pause_all = Arc::clone(&download_manager);
+		let tx_pause_all = tx.clone();
+		let pause_btn_all = pause_button;
+		pause_all_button.on_click(move |_| {
+			let queued = dm_pause_all.queued_jobs();
+			let selected = selected_job(&list_pause_all, &ids_pause_all);
+			let paused = dm_pause_all.pause_all();
+			if selected.is_some_and(|s| paused.contains(&s) && !queued.contains(&s)) {
+				pause_btn_all.enable(false);
+			}
+			let ids = ids_pause_all.lock().expect("IDs lock failed");
+			for id in paused.into_iter().filter(|id| queued.contains(id)) {
+				if let Some(pos) = ids.iter().position(|t| *t == id) {
+					list_pause_all.set_item_text_by_column(pos as i64, 1, "");
+				}
+				let _ = tx_pause_all.send(AppEvent::Paused(id));
+			}
+			let _ = tx_pause_all.send(AppEvent::QueueChanged);
+		});
+
+		let dm_resume_all = Arc::clone(&download_manager);
+		let tx_resume_all = tx.clone();
+		resume_all_button.on_click(move |_| {
+			for id in dm_resume_all.resume_all() {
+				let _ = tx_resume_all.send(AppEvent::Resumed(id));
+			}
+		});
+
+		let list_open_log = status_list;
+		let ids_open_log = Arc::clone(&download_list_ids);
+		let dm_open_log = Arc::clone(&download_manager);
+		let frame_open_log = frame;
+		open_log_button.on_click(move |_| {
+			let Some(path) = selected_job(&list_open_log, &ids_open_log).and_then(|id| dm_open_log.get_log_path(id))
+			else {
+				return;
+			};
+			if let Err(e) = platform::open_path(&path) {
+				let _ = MessageDialog::builder(&frame_open_log, &format!("Could not open log: {}", e), "Error")
+					.build()
+					.show_modal();
+			}
+		});
+
+		let list_export_log = status_list;
+		let ids_export_log = Arc::clone(&download_list_ids);
+		let dm_export_log = Arc::clone(&download_manager);
+		let frame_export_log = frame;
+		export_log_button.on_click(move |_| {
+			let Some(path) =
+				selected_job(&list_export_log, &ids_export_log).and_then(|id| dm_export_log.get_log_path(id))
+			else {
+				return;
+			};
+			let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+			let dialog = FileDialog::builder(&frame_export_log)
+				.with_message("Export Log")
+				.with_default_file(&file_name)
+				.with_wildcard(LOG_WILDCARD)
+				.with_style(FileDialogStyle::Save | FileDialogStyle::OverwritePrompt)
+				.build();
+			if dialog.show_modal() == wxdragon::id::ID_OK
+				&& let Some(target) = dialog.get_path()
+				&& let Err(e) = std::fs::copy(&path, &target)
+			{
+				let _ = MessageDialog::builder(&frame_export_log, &format!("Export failed: {}", e), "Error")
+					.build()
+					.show_modal();
 			}
 		});
 
 		let status_list_cancel = status_list;
 		let dm_cancel = Arc::clone(&download_manager);
+		let tx_cancel = tx.clone();
+		let cfg_cancel = Arc::clone(&config_manager);
 		cancel_button.on_click(move |_| {
-			if let Some(idx) = status_list_cancel.get_selection()
-				&& let Some(ids) = ids_cancel.lock().ok()
-				&& let Some(tag) = ids.get(idx as usize)
-				&& dm_cancel.has_task(tag)
+			let Some(id) = selected_job(&status_list_cancel, &ids_cancel) else {
+				return;
+			};
+			let keep_partial_files = cfg_cancel.lock().map(|c| c.get_keep_partial_files()).unwrap_or(true);
+			if dm_cancel.dequeue(id) {
+				let _ = tx_cancel.send(AppEvent::Error(id, "Removed from queue".into()));
+				let _ = tx_cancel.send(AppEvent::QueueChanged);
+			} else if dm_cancel.has_task(id) {
+				dm_cancel.cancel_task(id, keep_partial_files);
+			} else if let Some(state) = dm_cancel.get_job(id).map(|j| j.state)
+				&& matches!(state, JobState::Paused | JobState::Running)
 			{
-				dm_cancel.cancel_task(tag);
+				dm_cancel.set_state(id, JobState::Cancelled);
+				if !keep_partial_files {
+					dm_cancel.remove_partial_files(id);
+				}
+				if state == JobState::Paused {
+					let _ = tx_cancel.send(AppEvent::Error(id, "Cancelled while paused".into()));
+				}
 			}
 		});
 
+		let dm_toggle = Arc::clone(&download_manager);
+		let toggle_btn = queue_toggle_button;
+		queue_toggle_button.on_click(move |_| {
+			let running = !dm_toggle.is_queue_running();
+			dm_toggle.set_queue_running(running);
+			toggle_btn.set_label(if running { "Stop Queue" } else { "Start Queue" });
+		});
+
+		for (button, offset) in [(move_up_button, -1), (move_down_button, 1), (prioritize_button, isize::MIN)] {
+			let list_move = status_list;
+			let ids_move = Arc::clone(&download_list_ids);
+			let dm_move = Arc::clone(&download_manager);
+			let tx_move = tx.clone();
+			button.on_click(move |_| {
+				if let Some(id) = selected_job(&list_move, &ids_move)
+					&& dm_move.move_queued(id, offset)
+				{
+					let _ = tx_move.send(AppEvent::QueueChanged);
+				}
+			});
+		}
+
 		let url_tc = url_text_ctrl;
 		let cmd_choice_clone = commands_choice;
 		let cfg_for_download = Arc::clone(&config_manager);
@@ -415,6 +866,16 @@ fn main() {
 	});
 }
 
+fn update_pause_button(button: &Button, state: Option<JobState>) {
+	button.set_label(if state == Some(JobState::Paused) { "Resume" } else { "Pause" });
+	button.enable(matches!(state, Some(JobState::Queued | JobState::Running | JobState::Paused)));
+}
+
+fn selected_job(list: &ListCtrl, ids: &Arc<Mutex<Vec<JobId>>>) -> Option<JobId> {
+	let idx = list.get_first_selected_item();
+	(idx >= 0).then(|| ids.lock().ok().and_then(|ids| ids.get(idx as usize).copied())).flatten()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn handle_download_action(
 	sel_idx: usize,
@@ -444,6 +905,7 @@ fn handle_download_action(
 		if sel == 0 && urls.len() > 1 {
 			let first_url_clone = urls[0].clone();
 			let all_urls_clone = urls.clone();
+			let job_id = dm.create_job(urls.clone(), None, None, None, JobState::FetchingInfo);
 			let tx_info_fetch = tx.clone();
 			let cfg_info_fetch = cfg.clone();
 
@@ -451,19 +913,18 @@ fn handle_download_action(
 				let (yt_dlp_path, global_flags) = match cfg_info_fetch.lock() {
 					Ok(c) => (c.get_yt_dlp_path(), c.get_global_flags()),
 					Err(_) => {
-						let _ =
-							tx_info_fetch.send(AppEvent::Error(first_url_clone.clone(), "Config lock failed".into()));
+						let _ = tx_info_fetch.send(AppEvent::Error(job_id, "Config lock failed".into()));
 						return;
 					}
 				};
-				let mut cmd = Command::new(&yt_dlp_path);
+				let mut cmd = platform::command(&yt_dlp_path);
 				let parsed_flags = shell_words::split(&global_flags)
 					.unwrap_or_else(|_| global_flags.split_whitespace().map(String::from).collect());
 				for arg in &parsed_flags {
 					cmd.arg(arg);
 				}
 				cmd.arg("--dump-json").arg(&first_url_clone);
-				cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).creation_flags(CREATE_NO_WINDOW);
+				cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 				match cmd.spawn() {
 					Ok(mut child) => {
 						let stdout = child.stdout.take();
@@ -481,15 +942,19 @@ fn handle_download_action(
 							}
 						}
 						if let Some(first_video_info) = videos.first().cloned() {
-							let _ = tx_info_fetch
-								.send(AppEvent::ShowOptionsForMultipleUrls(all_urls_clone, Box::new(first_video_info)));
+							let _ = tx_info_fetch.send(AppEvent::ShowOptionsForMultipleUrls(
+								job_id,
+								all_urls_clone,
+								Box::new(first_video_info),
+							));
+							let _ = tx_info_fetch.send(AppEvent::Finished(job_id));
 						} else {
 							let _ = tx_info_fetch
-								.send(AppEvent::Error(first_url_clone, "No video info found for first URL.".into()));
+								.send(AppEvent::Error(job_id, "No video info found for first URL.".into()));
 						}
 					}
 					Err(e) => {
-						let _ = tx_info_fetch.send(AppEvent::Error(first_url_clone, format!("Spawn failed: {}", e)));
+						let _ = tx_info_fetch.send(AppEvent::Error(job_id, format!("Spawn failed: {}", e)));
 					}
 				}
 			});
@@ -498,10 +963,11 @@ fn handle_download_action(
 				if sel == 0 {
 					fetch_info(url, tx.clone(), cfg.clone(), Arc::clone(&dm));
 				} else if !cmd_str.is_empty() {
-					start_batch_download(
+					queue_download(
 						vec![url],
 						Some(cmd_str.clone()),
 						None,
+						None,
 						tx.clone(),
 						Arc::clone(&dm),
 						cfg.clone(),
@@ -530,6 +996,7 @@ fn handle_download_action(
 				.collect();
 			let first_url_clone = urls[0].clone();
 			let all_urls_clone = urls.clone();
+			let job_id = dm.create_job(urls.clone(), None, None, None, JobState::FetchingInfo);
 			let tx_info_fetch = tx.clone();
 			let cfg_info_fetch = cfg.clone();
 
@@ -537,19 +1004,18 @@ fn handle_download_action(
 				let (yt_dlp_path, global_flags) = match cfg_info_fetch.lock() {
 					Ok(c) => (c.get_yt_dlp_path(), c.get_global_flags()),
 					Err(_) => {
-						let _ =
-							tx_info_fetch.send(AppEvent::Error(first_url_clone.clone(), "Config lock failed".into()));
+						let _ = tx_info_fetch.send(AppEvent::Error(job_id, "Config lock failed".into()));
 						return;
 					}
 				};
-				let mut cmd = Command::new(&yt_dlp_path);
+				let mut cmd = platform::command(&yt_dlp_path);
 				let parsed_flags = shell_words::split(&global_flags)
 					.unwrap_or_else(|_| global_flags.split_whitespace().map(String::from).collect());
 				for arg in &parsed_flags {
 					cmd.arg(arg);
 				}
 				cmd.arg("--dump-json").arg(&first_url_clone);
-				cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).creation_flags(CREATE_NO_WINDOW);
+				cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 				match cmd.spawn() {
 					Ok(mut child) => {
 						let stdout = child.stdout.take();
@@ -567,17 +1033,19 @@ fn handle_download_action(
 							}
 						}
 						if let Some(first_video_info) = videos.first().cloned() {
-							let _ = tx_info_fetch
-								.send(AppEvent::ShowOptionsForMultipleUrls(all_urls_clone, Box::new(first_video_info)));
-						} else {
-							let _ = tx_info_fetch.send(AppEvent::Error(
-								first_url_clone,
-								"No video info found for first selected item.".into(),
+							let _ = tx_info_fetch.send(AppEvent::ShowOptionsForMultipleUrls(
+								job_id,
+								all_urls_clone,
+								Box::new(first_video_info),
 							));
+							let _ = tx_info_fetch.send(AppEvent::Finished(job_id));
+						} else {
+							let _ = tx_info_fetch
+								.send(AppEvent::Error(job_id, "No video info found for first selected item.".into()));
 						}
 					}
 					Err(e) => {
-						let _ = tx_info_fetch.send(AppEvent::Error(first_url_clone, format!("Spawn failed: {}", e)));
+						let _ = tx_info_fetch.send(AppEvent::Error(job_id, format!("Spawn failed: {}", e)));
 					}
 				}
 			});
@@ -589,10 +1057,11 @@ fn handle_download_action(
 				if sel == 0 {
 					fetch_info(url, tx.clone(), cfg.clone(), Arc::clone(&dm));
 				} else if !cmd_str.is_empty() {
-					start_batch_download(
+					queue_download(
 						vec![url],
 						Some(cmd_str.clone()),
 						None,
+						None,
 						tx.clone(),
 						Arc::clone(&dm),
 						cfg.clone(),
@@ -603,60 +1072,33 @@ fn handle_download_action(
 	}
 }
 
-fn expand_env_vars(arg: &str) -> String {
-	let mut expanded = arg.to_string();
-	let mut start = 0;
-	while let Some(pos) = expanded[start..].find('%') {
-		let actual_pos = start + pos;
-		if let Some(end_pos) = expanded[actual_pos + 1..].find('%') {
-			let actual_end = actual_pos + 1 + end_pos;
-			let var_name = &expanded[actual_pos + 1..actual_end];
-			if let Ok(val) = std::env::var(var_name) {
-				expanded.replace_range(actual_pos..actual_end + 1, &val);
-				start = actual_pos + val.len();
-			} else {
-				start = actual_end + 1;
-			}
-		} else {
-			break;
-		}
-	}
-	if cfg!(windows) {
-		expanded = expanded.replace("/", "\\");
-	}
-	expanded
-}
-
 fn fetch_info(
 	url: String,
 	tx: mpsc::Sender<AppEvent>,
 	cfg: Arc<Mutex<config::ConfigManager>>,
 	dm: Arc<DownloadManager>,
 ) {
+	let id = dm.create_job(vec![url.clone()], None, None, None, JobState::FetchingInfo);
 	let (yt_dlp_path, global_flags) = match cfg.lock() {
 		Ok(c) => (c.get_yt_dlp_path(), c.get_global_flags()),
 		Err(_) => {
-			let _ = tx.send(AppEvent::Error(url.clone(), "Configuration lock failed".into()));
+			let _ = tx.send(AppEvent::Error(id, "Configuration lock failed".into()));
 			return;
 		}
 	};
-	let tag = url.clone();
-	let _ = tx.send(AppEvent::Status(tag.clone(), format!("Fetching info: {}", url)));
+	let _ = tx.send(AppEvent::Status(id, format!("Fetching info: {}", url)));
 	let _ = tx.send(AppEvent::Output(
-		tag.clone(),
+		id,
 		format!("Running: {} {} --dump-json --flat-playlist \"{}\"", yt_dlp_path, global_flags, url),
 	));
 
 	let tx_out = tx.clone();
 	let tx_err = tx.clone();
-	let tag_out = tag.clone();
-	let tag_err = tag.clone();
 	let dm_clone = dm.clone();
-	let tag_cleanup = tag.clone();
 	let yt_path = yt_dlp_path.clone();
 
 	thread::spawn(move || {
-		let mut cmd = Command::new(&yt_dlp_path);
+		let mut cmd = platform::command(&yt_dlp_path);
 		cmd.env("PYTHONIOENCODING", "utf-8");
 		cmd.arg("--encoding").arg("utf-8");
 		let parsed_flags = shell_words::split(&global_flags)
@@ -665,18 +1107,19 @@ fn fetch_info(
 			cmd.arg(expand_env_vars(arg));
 		}
 		cmd.arg("--dump-json").arg("--flat-playlist").arg(&url);
-		cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).creation_flags(CREATE_NO_WINDOW);
+		cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+		platform::isolate_process_group(&mut cmd);
 
 		match cmd.spawn() {
 			Ok(mut child) => {
 				let stdout = child.stdout.take();
 				let stderr = child.stderr.take();
-				let shared_child = dm.register_task(tag_out.clone(), child);
+				let shared_child = dm.register_task(id, child);
 
 				if let Some(err) = stderr {
 					thread::spawn(move || {
 						for l in BufReader::new(err).lines().map_while(Result::ok) {
-							let _ = tx_err.send(AppEvent::Output(tag_err.clone(), format!("[Info] {}", l)));
+							let _ = tx_err.send(AppEvent::Output(id, format!("[Info] {}", l)));
 						}
 					});
 				}
@@ -691,12 +1134,11 @@ fn fetch_info(
 							match serde_json::from_str::<VideoInfo>(&l) {
 								Ok(info) => videos.push(info),
 								Err(e) => {
-									let _ = tx_out
-										.send(AppEvent::Output(tag_out.clone(), format!("JSON Parse Warning: {}", e)));
+									let _ = tx_out.send(AppEvent::Output(id, format!("JSON Parse Warning: {}", e)));
 								}
 							}
 						} else {
-							let _ = tx_out.send(AppEvent::Output(tag_out.clone(), l));
+							let _ = tx_out.send(AppEvent::Output(id, l));
 						}
 					}
 				}
@@ -717,24 +1159,19 @@ fn fetch_info(
 					};
 
 					if killed {
-						let _ = tx_out.send(AppEvent::Error(tag_out.clone(), "Cancelled".into()));
+						let _ = tx_out.send(AppEvent::Error(id, "Cancelled".into()));
 						break;
 					}
 					if let Some(s) = status {
 						if s.success() {
 							if !videos.is_empty() {
 								if videos.first().is_some_and(|v| v.formats.is_empty()) {
-									let _ = tx_out
-										.send(AppEvent::Status(tag_out.clone(), "Fetching detailed formats...".into()));
-									let mut cmd2 = Command::new(&yt_path);
+									let _ = tx_out.send(AppEvent::Status(id, "Fetching detailed formats...".into()));
+									let mut cmd2 = platform::command(&yt_path);
 									for arg in &parsed_flags {
 										cmd2.arg(expand_env_vars(arg));
 									}
-									cmd2.arg("--dump-json")
-										.arg("--playlist-items")
-										.arg("1")
-										.arg(&url)
-										.creation_flags(CREATE_NO_WINDOW);
+									cmd2.arg("--dump-json").arg("--playlist-items").arg("1").arg(&url);
 									if let Ok(output) = cmd2.output()
 										&& output.status.success() && let Ok(full_json) =
 										String::from_utf8(output.stdout)
@@ -744,37 +1181,35 @@ fn fetch_info(
 										videos[0] = ref_video;
 									}
 								}
-								let _ = tx_out.send(AppEvent::ShowOptions(tag_out.clone(), videos));
-								let _ = tx_out.send(AppEvent::Finished(tag_out.clone()));
+								let _ = tx_out.send(AppEvent::ShowOptions(id, url.clone(), videos));
+								let _ = tx_out.send(AppEvent::Finished(id));
 							} else {
-								let _ = tx_out
-									.send(AppEvent::Error(tag_out.clone(), "No valid video information found.".into()));
+								let _ = tx_out.send(AppEvent::Error(id, "No valid video information found.".into()));
 							}
 						} else {
-							let _ = tx_out.send(AppEvent::Error(
-								tag_out.clone(),
-								format!("Process exited with code {:?}", s.code()),
-							));
+							let _ =
+								tx_out.send(AppEvent::Error(id, format!("Process exited with code {:?}", s.code())));
 						}
 						break;
 					}
-					if !dm_clone.has_task(&tag_out) {
+					if !dm_clone.has_task(id) {
 						break;
 					}
 				}
-				dm_clone.unregister_task(&tag_cleanup);
+				dm_clone.unregister_task(id);
 			}
 			Err(e) => {
-				let _ = tx_out.send(AppEvent::Error(tag_out, format!("Spawn failed: {}", e)));
+				let _ = tx_out.send(AppEvent::Error(id, format!("Spawn failed: {}", e)));
 			}
 		}
 	});
 }
 
-fn start_batch_download(
+fn queue_download(
 	urls: Vec<String>,
 	custom_cmd: Option<String>,
 	opts: Option<DownloadOptions>,
+	output_dir: Option<String>,
 	tx: mpsc::Sender<AppEvent>,
 	dm: Arc<DownloadManager>,
 	cfg: Arc<Mutex<config::ConfigManager>>,
@@ -782,24 +1217,74 @@ fn start_batch_download(
 	if urls.is_empty() {
 		return;
 	}
-	let tag = urls[0].clone();
-	let status_msg = if urls.len() > 1 {
-		format!("Started batch of {}: {}", urls.len(), &tag)
-	} else {
-		format!("Started: {}", &tag)
+	let (output_dir, retry) = match cfg.lock() {
+		Ok(c) => (
+			output_dir
+				.or_else(|| opts.as_ref().and_then(|o| o.destination.clone()))
+				.or(c.get_download_path())
+				.filter(|p| !p.is_empty()),
+			c.get_retry_policy(),
+		),
+		Err(_) => (output_dir, Default::default()),
 	};
-	let _ = tx.send(AppEvent::Status(tag.clone(), status_msg));
+	let id = dm.create_job(urls, opts, custom_cmd, output_dir, JobState::Queued);
+	dm.update_job(id, |j| j.retry = retry);
+	dm.enqueue(id);
+	let label = dm.get_job(id).map(|j| j.label()).unwrap_or_default();
+	let _ = tx.send(AppEvent::Status(id, format!("Queued: {}", label)));
+	let _ = tx.send(AppEvent::QueueChanged);
+}
 
-	let (yt_dlp_path, download_path, ffmpeg_path, global_flags) = if let Ok(c) = cfg.lock() {
-		(c.get_yt_dlp_path(), c.get_download_path(), c.get_ffmpeg_path(), c.get_global_flags())
-	} else {
-		let _ = tx.send(AppEvent::Error(tag, "Config lock failed".into()));
+fn start_queued_download(
+	id: JobId,
+	tx: mpsc::Sender<AppEvent>,
+	dm: Arc<DownloadManager>,
+	cfg: Arc<Mutex<config::ConfigManager>>,
+) {
+	let Some(job) = dm.get_job(id) else {
 		return;
 	};
+	let (urls, opts, custom_cmd, download_path, resumed, retry) =
+		(job.urls, job.options, job.custom_command, job.output_dir, job.resumed, job.retry);
+	let info_path = job.info_path.unwrap_or_else(|| dm.job_info_path(id));
+	dm.update_job(id, |j| j.info_path = Some(info_path.clone()));
+	let status_msg = if urls.len() > 1 {
+		format!("Started batch of {}: {}", urls.len(), &urls[0])
+	} else {
+		format!("Started: {}", &urls[0])
+	};
+	let _ = tx.send(AppEvent::Status(id, status_msg));
+
+	let template_kind = opts.as_ref().map(TemplateKind::for_options).unwrap_or(TemplateKind::Single);
+	let (yt_dlp_path, ffmpeg_path, global_flags, archive_path, check_format, sponsorblock_api, output_template) =
+		if let Ok(c) = cfg.lock() {
+			(
+				c.get_yt_dlp_path(),
+				c.get_ffmpeg_path(),
+				c.get_global_flags(),
+				c.archive_path(download_path.as_deref()),
+				c.get_check_format_selector(),
+				c.get_sponsorblock_api(),
+				c.get_output_template(template_kind),
+			)
+		} else {
+			let _ = tx.send(AppEvent::Error(id, "Config lock failed".into()));
+			return;
+		};
+	let output_template = match opts.as_ref().and_then(|o| o.sections.as_ref()) {
+		Some(sections) if sections.ranges.len() > 1 => {
+			Some(output_template::with_section_suffix(if output_template.is_empty() {
+				output_template::DEFAULT_TEMPLATE
+			} else {
+				&output_template
+			}))
+		}
+		_ => Some(output_template).filter(|t| !t.is_empty()),
+	};
 
 	let urls_clone = urls.clone();
 	thread::spawn(move || {
-		let mut cmd = Command::new(&yt_dlp_path);
+		let mut cmd = platform::command(&yt_dlp_path);
 		cmd.env("PYTHONIOENCODING", "utf-8");
 		cmd.arg("--encoding").arg("utf-8");
 		let args_iter = shell_words::split(&global_flags)
@@ -807,12 +1292,27 @@ fn start_batch_download(
 		for arg in args_iter {
 			cmd.arg(expand_env_vars(&arg));
 		}
+		cmd.arg("--newline").arg("--progress-template").arg(progress::progress_template());
+		if let Some(dir) = info_path.parent() {
+			let _ = std::fs::create_dir_all(dir);
+		}
+		cmd.arg("--print-to-file").arg(history::PRINT_TEMPLATE).arg(info_path.to_string_lossy().replace('%', "%%"));
+		if resumed {
+			cmd.arg("--continue");
+		}
+		if let Some(archive) = &archive_path {
+			cmd.arg("--download-archive").arg(archive);
+		}
 
 		if let Some(dp) = download_path
 			&& !dp.is_empty()
 		{
+			let _ = std::fs::create_dir_all(&dp);
 			cmd.current_dir(dp);
 		}
+		if let Some(template) = &output_template {
+			cmd.arg("-o").arg(template);
+		}
 		if !ffmpeg_path.is_empty() && ffmpeg_path != "ffmpeg" {
 			let p = std::path::Path::new(&ffmpeg_path);
 			if p.is_absolute() || p.parent().is_some_and(|parent| !parent.as_os_str().is_empty()) {
@@ -820,79 +1320,42 @@ fn start_batch_download(
 			}
 		}
 
-		if let Some(o) = opts {
-			let (v_format, a_formats) = match &o.mode {
-				DownloadMode::Single { video_format, audio_formats } => (video_format.clone(), audio_formats.clone()),
-				DownloadMode::Playlist { video_format, audio_formats, .. } => {
-					(video_format.clone(), audio_formats.clone())
-				}
-			};
-			let get_base_fmt = |f: &str| f.split('-').next().unwrap_or(f).to_string();
-			let mut primary_f = String::new();
-			let mut fuzzy_f = String::new();
-			let mut lang_f = String::new();
-
-			if let Some(v) = &v_format {
-				primary_f.push_str(v);
-				let v_base = get_base_fmt(v);
-				lang_f.push_str(&if !a_formats.is_empty() {
-					format!("bv[format_id^='{}']", v_base)
-				} else {
-					format!("b[format_id^='{}']", v_base)
-				});
-				fuzzy_f.push_str(if !a_formats.is_empty() { "bv" } else { "b" });
+		let mut selector = None;
+		if let Some(o) = &opts {
+			if let Some(f_selector) = format_selector::build(o) {
+				cmd.arg("-f").arg(&f_selector);
+				dm.update_job(id, |j| j.format_selector = Some(f_selector.clone()));
+				let _ = tx.send(AppEvent::Output(id, format!("Format Selector: {}", f_selector)));
+				selector = Some(f_selector);
 			}
 
-			if !a_formats.is_empty() {
-				if !primary_f.is_empty() {
-					primary_f.push('+');
-					fuzzy_f.push('+');
-					lang_f.push('+');
-				}
-				primary_f.push_str(&a_formats.join("+"));
-				fuzzy_f.push_str(
-					&a_formats
-						.iter()
-						.map(|a| format!("ba[format_id^='{}']", get_base_fmt(a)))
-						.collect::<Vec<_>>()
-						.join("+"),
-				);
-				if o.preferred_languages.len() == a_formats.len() {
-					lang_f.push_str(
-						&o.preferred_languages
-							.iter()
-							.zip(a_formats.iter())
-							.map(|(l, f)| format!("ba[language='{}'][format_id^='{}']", l, get_base_fmt(f)))
-							.collect::<Vec<_>>()
-							.join("+"),
-					);
-				} else {
-					lang_f.clear();
-				}
-			} else {
-				lang_f.clear();
+			if let Some(preset) = &o.preset
+				&& !preset.sort.is_empty()
+			{
+				cmd.arg("-S").arg(&preset.sort);
 			}
-
-			if !primary_f.is_empty() {
-				let mut f_selector = String::new();
-				if !lang_f.is_empty() {
-					f_selector.push_str(&lang_f);
-					f_selector.push('/');
-				}
-				f_selector.push_str(&primary_f);
-				f_selector.push('/');
-				f_selector.push_str(&fuzzy_f);
-				cmd.arg("-f").arg(&f_selector);
-				let _ = tx.send(AppEvent::Output(tag.clone(), format!("Format Selector: {}", f_selector)));
+			if let Some(extraction) = &o.extract_audio {
+				cmd.args(extraction.args());
+			}
+			if let Some(subtitles) = &o.subtitles {
+				cmd.args(subtitles.args());
+			}
+			if let Some(sections) = &o.sections {
+				cmd.args(sections.args());
+			}
+			if let Some(sponsorblock) = &o.sponsorblock {
+				cmd.args(sponsorblock.args(&sponsorblock_api));
+			}
+			if let Some(metadata) = &o.metadata {
+				cmd.args(metadata.args());
 			}
-
 			if let DownloadMode::Playlist { indices, .. } = &o.mode {
 				cmd.arg("--playlist-items").arg(indices.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(","));
 			}
 			if o.add_chapters {
 				cmd.arg("--embed-chapters");
 			}
-			if o.multi_audio || a_formats.len() > 1 {
+			if o.multi_audio || o.mode.formats().1.len() > 1 {
 				cmd.arg("--audio-multistreams");
 			}
 		}
@@ -909,75 +1372,202 @@ fn start_batch_download(
 		}
 
 		let cmd_str = format!("{:?} {:?}", cmd.get_program(), cmd.get_args().collect::<Vec<_>>());
-		let _ = tx.send(AppEvent::Output(tag.clone(), format!("Executing: {}", cmd_str)));
-
-		cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).creation_flags(CREATE_NO_WINDOW);
-
-		match cmd.spawn() {
-			Ok(mut child) => {
-				let stdout = child.stdout.take();
-				let stderr = child.stderr.take();
-				let shared_child = dm.register_task(tag.clone(), child);
-				let tx_out = tx.clone();
-				let tag_out = tag.clone();
-				if let Some(out) = stdout {
-					thread::spawn(move || {
-						for l in BufReader::new(out).lines().map_while(Result::ok) {
-							let _ = tx_out.send(AppEvent::Output(tag_out.clone(), l));
+		let _ = tx.send(AppEvent::Output(id, format!("Executing: {}", cmd_str)));
+
+		cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+		platform::isolate_process_group(&mut cmd);
+
+		if check_format && let (Some(sel), Some(o), Some(url)) = (&selector, &opts, urls_clone.first()) {
+			let _ = tx.send(AppEvent::Output(id, "[Format Check] Checking selector with yt-dlp --simulate".into()));
+			let mut check = format_check_command(&yt_dlp_path, &global_flags, sel, o, url);
+			check.stdout(Stdio::null()).stderr(Stdio::piped());
+			platform::isolate_process_group(&mut check);
+			match check.spawn() {
+				Ok(mut child) => {
+					let stderr = child.stderr.take();
+					let shared_child = dm.register_task(id, child);
+					let errors: Vec<String> =
+						stderr.map(|e| BufReader::new(e).lines().map_while(Result::ok).collect()).unwrap_or_default();
+					let status = shared_child.lock().expect("Child lock failed").wait();
+					dm.unregister_task(id);
+					if dm.get_job(id).is_none_or(|j| j.state != JobState::Running) {
+						let _ = tx.send(AppEvent::Error(id, "Format check aborted".into()));
+						return;
+					}
+					if !status.is_ok_and(|s| s.success()) {
+						for e in &errors {
+							let _ = tx.send(AppEvent::Output(id, format!("[Format Check] {}", e)));
 						}
-					});
+						let reason = errors.last().cloned().unwrap_or_else(|| "yt-dlp exited with an error".into());
+						let _ = tx.send(AppEvent::Error(id, format!("Format selector rejected: {}", reason)));
+						return;
+					}
+					let _ = tx.send(AppEvent::Output(id, "[Format Check] Selector accepted".into()));
 				}
-				let tx_err = tx.clone();
-				let tag_err = tag.clone();
-				if let Some(err) = stderr {
-					thread::spawn(move || {
-						for l in BufReader::new(err).lines().map_while(Result::ok) {
-							let _ = tx_err.send(AppEvent::Output(tag_err.clone(), format!("[Err] {}", l)));
-						}
-					});
+				Err(e) => {
+					let _ =
+						tx.send(AppEvent::Output(id, format!("[Format Check] Skipped, could not run yt-dlp: {}", e)));
 				}
+			}
+		}
 
-				loop {
-					thread::sleep(std::time::Duration::from_millis(200));
-					let mut killed = false;
-					let status = {
-						let mut c = shared_child.lock().expect("Child lock failed");
-						match c.try_wait() {
-							Ok(Some(s)) => Some(s),
-							Ok(None) => None,
-							Err(_) => {
-								killed = true;
-								None
+		let mut attempt = 1;
+		loop {
+			match cmd.spawn() {
+				Ok(mut child) => {
+					let stdout = child.stdout.take();
+					let stderr = child.stderr.take();
+					let shared_child = dm.register_task(id, child);
+					let tx_out = tx.clone();
+					let dm_out = Arc::clone(&dm);
+					if let Some(out) = stdout {
+						thread::spawn(move || {
+							let mut last_progress: Option<Instant> = None;
+							for l in BufReader::new(out).lines().map_while(Result::ok) {
+								if let Some(path) = progress::parse_destination(&l) {
+									dm_out.record_output_file(id, path);
+								}
+								if let Some(p) = ProgressInfo::parse(&l) {
+									if p.status != "downloading"
+										|| last_progress.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL)
+									{
+										last_progress = Some(Instant::now());
+										let _ = tx_out.send(AppEvent::Progress(id, p));
+									}
+									continue;
+								}
+								let _ = tx_out.send(AppEvent::Output(id, l));
+							}
+						});
+					}
+					let tx_err = tx.clone();
+					let err_reader = stderr.map(|err| {
+						thread::spawn(move || {
+							let mut error_class = None;
+							for l in BufReader::new(err).lines().map_while(Result::ok) {
+								error_class = ErrorClass::classify(&l).or(error_class);
+								let _ = tx_err.send(AppEvent::Output(id, format!("[Err] {}", l)));
 							}
+							error_class
+						})
+					});
+
+					let mut failure = None;
+					loop {
+						thread::sleep(std::time::Duration::from_millis(200));
+						let mut killed = false;
+						let status = {
+							let mut c = shared_child.lock().expect("Child lock failed");
+							match c.try_wait() {
+								Ok(Some(s)) => Some(s),
+								Ok(None) => None,
+								Err(_) => {
+									killed = true;
+									None
+								}
+							}
+						};
+						if killed {
+							failure = Some("Process killed".to_string());
+							break;
+						}
+						if let Some(s) = status {
+							if s.success() {
+								let _ = tx.send(AppEvent::Finished(id));
+							} else {
+								failure = Some(format!("Exit: {:?}", s));
+							}
+							break;
 						}
+						if !dm.has_task(id) {
+							break;
+						}
+					}
+					dm.unregister_task(id);
+
+					let Some(failure) = failure else {
+						break;
 					};
-					if killed {
-						let _ = tx.send(AppEvent::Error(tag.clone(), "Process killed".into()));
+					let error_class = err_reader.and_then(|r| r.join().ok()).flatten();
+					let still_running = || dm.get_job(id).is_some_and(|j| j.state == JobState::Running);
+					if !still_running() || !retry.should_retry(attempt, error_class) {
+						let _ = tx.send(AppEvent::Error(id, failure));
 						break;
 					}
-					if let Some(s) = status {
-						if s.success() {
-							let _ = tx.send(AppEvent::Finished(tag.clone()));
-						} else {
-							let _ = tx.send(AppEvent::Error(tag.clone(), format!("Exit: {:?}", s)));
-						}
-						break;
+					let delay = retry.delay(attempt);
+					let _ = tx.send(AppEvent::Output(
+						id,
+						format!(
+							"[Retry] Attempt {}/{} failed ({}): {}. Retrying in {}s",
+							attempt,
+							retry.max_attempts,
+							error_class.map(|c| c.label()).unwrap_or_default(),
+							failure,
+							delay.as_secs()
+						),
+					));
+					let _ = tx.send(AppEvent::Retrying(
+						id,
+						format!("Retrying in {}s (attempt {}/{})", delay.as_secs(), attempt + 1, retry.max_attempts),
+					));
+					let deadline = Instant::now() + delay;
+					while Instant::now() < deadline && still_running() {
+						thread::sleep(std::time::Duration::from_millis(200));
 					}
-					if !dm.has_task(&tag) {
+					if !still_running() {
+						let _ = tx.send(AppEvent::Error(id, "Retry aborted".into()));
 						break;
 					}
+					attempt += 1;
+					let _ = tx.send(AppEvent::Output(
+						id,
+						format!("[Retry] Starting attempt {}/{}", attempt, retry.max_attempts),
+					));
+				}
+				Err(e) => {
+					let _ = tx.send(AppEvent::Error(id, format!("Spawn failed: {}", e)));
+					break;
 				}
-				dm.unregister_task(&tag);
-			}
-			Err(e) => {
-				let _ = tx.send(AppEvent::Error(tag, format!("Spawn failed: {}", e)));
 			}
 		}
 	});
 }
 
+fn format_check_command(
+	yt_dlp_path: &str,
+	global_flags: &str,
+	selector: &str,
+	opts: &DownloadOptions,
+	url: &str,
+) -> std::process::Command {
+	let mut cmd = platform::command(yt_dlp_path);
+	cmd.env("PYTHONIOENCODING", "utf-8");
+	cmd.arg("--encoding").arg("utf-8");
+	let args_iter = shell_words::split(global_flags)
+		.unwrap_or_else(|_| global_flags.split_whitespace().map(String::from).collect());
+	for arg in args_iter {
+		cmd.arg(expand_env_vars(&arg));
+	}
+	cmd.arg("--simulate").arg("--quiet").arg("--no-warnings").arg("-f").arg(selector);
+	if let Some(preset) = &opts.preset
+		&& !preset.sort.is_empty()
+	{
+		cmd.arg("-S").arg(&preset.sort);
+	}
+	if opts.multi_audio || opts.mode.formats().1.len() > 1 {
+		cmd.arg("--audio-multistreams");
+	}
+	if let DownloadMode::Playlist { indices, .. } = &opts.mode
+		&& let Some(first) = indices.first()
+	{
+		cmd.arg("--playlist-items").arg(first.to_string());
+	}
+	cmd.arg(url);
+	cmd
+}
+
 fn show_setup_dialog(parent: &impl WxWidget, (yt_ok, ff_ok): (bool, bool)) -> i32 {
-	let dialog = Dialog::builder(parent, "Setup Required").with_size(400, 250).build();
+	let dialog = Dialog::builder(parent, "Setup Required").with_size(520, 250).build();
 	let sizer = BoxSizer::builder(Orientation::Vertical).build();
 	let mut missing = Vec::new();
 	if !yt_ok {
@@ -987,21 +1577,25 @@ fn show_setup_dialog(parent: &impl WxWidget, (yt_ok, ff_ok): (bool, bool)) -> i3
 		missing.push("ffmpeg");
 	}
 	let msg_text = format!(
-		"Tubex requires yt-dlp and ffmpeg to function.\nThe following were not found in your configuration or system PATH:\n\n- {}\n\nWould you like Tubex to download and configure them automatically?",
+		"Tubex requires yt-dlp and ffmpeg to function.\nThe following were not found in your configuration or system PATH:\n\n- {}\n\nWould you like Tubex to download and configure them automatically, or install them from an archive you already have?",
 		missing.join("\n- ")
 	);
 	let msg = StaticText::builder(&dialog).with_label(&msg_text).build();
 	sizer.add(&msg, 1, SizerFlag::Expand | SizerFlag::All, 10);
 	let btn_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 	let download_btn = Button::builder(&dialog).with_label("Download (Auto)").build();
+	let archive_btn = Button::builder(&dialog).with_label("Install from local archive...").build();
 	let settings_btn = Button::builder(&dialog).with_label("Configure Manually").build();
 	btn_sizer.add_stretch_spacer(1);
 	btn_sizer.add(&download_btn, 0, SizerFlag::All, 5);
+	btn_sizer.add(&archive_btn, 0, SizerFlag::All, 5);
 	btn_sizer.add(&settings_btn, 0, SizerFlag::All, 5);
 	btn_sizer.add_stretch_spacer(1);
 	sizer.add_sizer(&btn_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
 	let d_dl = dialog;
 	download_btn.on_click(move |_| d_dl.end_modal(2));
+	let d_archive = dialog;
+	archive_btn.on_click(move |_| d_archive.end_modal(3));
 	let d_set = dialog;
 	settings_btn.on_click(move |_| d_set.end_modal(1));
 	dialog.set_sizer(sizer, true);
@@ -1,28 +1,31 @@
+use std::{
+	cell::RefCell,
+	collections::{BTreeMap, HashSet},
+	rc::Rc,
+	sync::{Arc, Mutex},
+};
+
 use wxdragon::{
-	ListColumnFormat, ListCtrlStyle, ListItemState, ListNextItemFlag, Orientation, PanelStyle,
+	ListColumnFormat, ListCtrlStyle, ListItemState, ListNextItemFlag, Orientation, PanelStyle, TextCtrlStyle,
 	prelude::*,
-	widgets::{CheckBox, ListCtrl, Notebook, Panel},
+	widgets::{CheckBox, Choice, ListCtrl, Notebook, Panel},
 };
 
-use crate::video_info::{Format, VideoInfo};
+use crate::{
+	config::ConfigManager,
+	config_dialog, destination, download_archive,
+	download_options::{
+		self, AUDIO_CODECS, AUDIO_QUALITIES, AudioExtraction, DownloadMode, DownloadOptions, DownloadSections,
+		MetadataOptions, SPONSORBLOCK_CATEGORIES, SUBTITLE_FORMATS, SponsorBlockOptions, SubtitleOptions,
+		THUMBNAIL_FORMATS, TimeRange,
+	},
+	presets::FormatPreset,
+	video_info::{Format, SubtitleTrack, VideoInfo},
+};
 
 const RET_OK: i32 = 1;
 const RET_CANCEL: i32 = 0;
 
-#[derive(Debug, Clone)]
-pub enum DownloadMode {
-	Single { video_format: Option<String>, audio_formats: Vec<String> },
-	Playlist { indices: Vec<usize>, video_format: Option<String>, audio_formats: Vec<String> },
-}
-
-#[derive(Debug, Clone)]
-pub struct DownloadOptions {
-	pub mode: DownloadMode,
-	pub add_chapters: bool,
-	pub multi_audio: bool,
-	pub preferred_languages: Vec<String>,
-}
-
 fn format_label(f: &Format) -> String {
 	let res = f.width.zip(f.height).map(|(w, h)| format!("{}x{}", w, h)).unwrap_or_else(|| "audio".to_string());
 	format!(
@@ -35,7 +38,447 @@ fn format_label(f: &Format) -> String {
 	)
 }
 
-pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<DownloadOptions> {
+fn fill_preset_choice(choice: &Choice, presets: &[FormatPreset]) {
+	choice.clear();
+	choice.append("Manual selection");
+	for preset in presets {
+		choice.append(&preset.name);
+	}
+	choice.set_selection(0);
+}
+
+fn create_preset_choice(dialog: &Dialog, sizer: &BoxSizer, presets: &[FormatPreset]) -> Choice {
+	let preset_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	preset_sizer.add(
+		&StaticText::builder(dialog).with_label("Preset:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let choice = Choice::builder(dialog).build();
+	fill_preset_choice(&choice, presets);
+	preset_sizer.add(&choice, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	sizer.add_sizer(&preset_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+	choice
+}
+
+fn selected_preset(choice: &Choice, presets: &[FormatPreset]) -> Option<FormatPreset> {
+	choice.get_selection().filter(|s| *s > 0).and_then(|s| presets.get(s as usize - 1).cloned())
+}
+
+#[derive(Clone, Copy)]
+pub struct AudioControls {
+	enabled: CheckBox,
+	codec: Choice,
+	quality: Choice,
+}
+
+impl AudioControls {
+	pub fn create(dialog: &Dialog, sizer: &BoxSizer) -> Self {
+		let row = BoxSizer::builder(Orientation::Horizontal).build();
+		let controls = Self {
+			enabled: CheckBox::builder(dialog).with_label("Extract audio").build(),
+			codec: Choice::builder(dialog).with_choices(AUDIO_CODECS.map(String::from).to_vec()).build(),
+			quality: Choice::builder(dialog).with_choices(AUDIO_QUALITIES.map(String::from).to_vec()).build(),
+		};
+		row.add(&controls.enabled, 0, SizerFlag::AlignCenterVertical | SizerFlag::All, 5);
+		for (label, choice) in [("Codec:", controls.codec), ("Quality:", controls.quality)] {
+			row.add(
+				&StaticText::builder(dialog).with_label(label).build(),
+				0,
+				SizerFlag::AlignCenterVertical | SizerFlag::All,
+				5,
+			);
+			row.add(&choice, 0, SizerFlag::All, 5);
+		}
+		sizer.add_sizer(&row, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+		controls.set(None);
+		controls.enabled.on_toggled(move |_| {
+			controls.codec.enable(controls.enabled.get_value());
+			controls.quality.enable(controls.enabled.get_value());
+		});
+		controls
+	}
+
+	pub fn set(&self, extraction: Option<&AudioExtraction>) {
+		let index = |values: &[&str], value: Option<&str>| {
+			value.and_then(|v| values.iter().position(|x| *x == v)).unwrap_or_default() as u32
+		};
+		self.enabled.set_value(extraction.is_some());
+		self.codec.set_selection(index(&AUDIO_CODECS, extraction.map(|x| x.codec.as_str())));
+		self.quality.set_selection(index(&AUDIO_QUALITIES, extraction.map(|x| x.quality.as_str())));
+		self.codec.enable(extraction.is_some());
+		self.quality.enable(extraction.is_some());
+	}
+
+	pub fn extraction(&self) -> Option<AudioExtraction> {
+		self.enabled.get_value().then(|| AudioExtraction {
+			codec: self.codec.get_string_selection().unwrap_or_else(|| AUDIO_CODECS[0].to_string()),
+			quality: self.quality.get_string_selection().unwrap_or_else(|| AUDIO_QUALITIES[0].to_string()),
+		})
+	}
+}
+
+fn create_save_preset_button(
+	dialog: &Dialog,
+	sizer: &BoxSizer,
+	config_manager: &Arc<Mutex<ConfigManager>>,
+	presets: &Rc<RefCell<Vec<FormatPreset>>>,
+	audio: AudioControls,
+	preset_choice: Choice,
+	format_panels: [Panel; 2],
+) {
+	let save_btn = Button::builder(dialog).with_label("Save Audio Preset...").build();
+	sizer.add(&save_btn, 0, SizerFlag::AlignRight | SizerFlag::All, 5);
+
+	let d_save = *dialog;
+	let cfg_save = Arc::clone(config_manager);
+	let presets_save = Rc::clone(presets);
+	save_btn.on_click(move |_| {
+		let Some(extraction) = audio.extraction() else {
+			let _ = MessageDialog::builder(&d_save, "Enable \"Extract audio\" first.", "Save Audio Preset")
+				.build()
+				.show_modal();
+			return;
+		};
+		let suggested = FormatPreset {
+			extract_audio: Some(extraction.clone()),
+			..FormatPreset::new(
+				&format!("Audio as {} {}", extraction.codec.to_uppercase(), extraction.quality),
+				"ba/b",
+				"",
+			)
+		};
+		if let Some(preset) = config_dialog::show_preset_dialog(&d_save, "Save Audio Preset", Some(&suggested)) {
+			let mut cfg = cfg_save.lock().expect("Config manager lock failed");
+			let mut all = cfg.get_presets();
+			all.retain(|p| p.name != preset.name);
+			all.push(preset);
+			cfg.set_presets(&all);
+			cfg.flush();
+			fill_preset_choice(&preset_choice, &all);
+			format_panels.iter().for_each(|p| p.enable(true));
+			*presets_save.borrow_mut() = all;
+		}
+	});
+}
+
+fn selected_items(list: &ListCtrl) -> Vec<usize> {
+	let mut items = Vec::new();
+	let mut item = list.get_next_item(-1, ListNextItemFlag::All, ListItemState::Selected);
+	while item != -1 {
+		items.push(item as usize);
+		item = list.get_next_item(item as i64, ListNextItemFlag::All, ListItemState::Selected);
+	}
+	items
+}
+
+fn create_subtitle_list(
+	panel: &Panel,
+	sizer: &BoxSizer,
+	label: &str,
+	tracks: &BTreeMap<String, Vec<SubtitleTrack>>,
+	preferred: &[String],
+) -> (ListCtrl, Vec<String>) {
+	sizer.add(&StaticText::builder(panel).with_label(label).build(), 0, SizerFlag::All, 5);
+	let list = ListCtrl::builder(panel).with_style(ListCtrlStyle::Report).build();
+	list.insert_column(0, "Language", ListColumnFormat::Left, 100);
+	list.insert_column(1, "Name", ListColumnFormat::Left, 220);
+	list.insert_column(2, "Formats", ListColumnFormat::Left, 200);
+	let languages: Vec<String> = tracks.keys().filter(|l| *l != "live_chat").cloned().collect();
+	for (i, lang) in languages.iter().enumerate() {
+		let row = i as i64;
+		let lang_tracks = &tracks[lang];
+		list.insert_item(row, lang, None);
+		list.set_item_text_by_column(row, 1, lang_tracks.iter().find_map(|t| t.name.as_deref()).unwrap_or_default());
+		list.set_item_text_by_column(
+			row,
+			2,
+			&lang_tracks.iter().filter_map(|t| t.ext.as_deref()).collect::<Vec<_>>().join(", "),
+		);
+		if preferred.contains(lang) {
+			list.set_item_state(row, ListItemState::Selected, ListItemState::Selected);
+		}
+	}
+	sizer.add(&list, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	(list, languages)
+}
+
+#[derive(Clone, Copy)]
+struct SubtitleControls {
+	format: Choice,
+	embed: Choice,
+}
+
+impl SubtitleControls {
+	fn create(panel: &Panel, sizer: &BoxSizer) -> Self {
+		let row = BoxSizer::builder(Orientation::Horizontal).build();
+		let controls = Self {
+			format: Choice::builder(panel).with_choices(SUBTITLE_FORMATS.map(String::from).to_vec()).build(),
+			embed: Choice::builder(panel)
+				.with_choices(vec!["Embed into the video".to_string(), "Write sidecar files".to_string()])
+				.build(),
+		};
+		controls.format.set_selection(0);
+		controls.embed.set_selection(0);
+		row.add(
+			&StaticText::builder(panel).with_label("Format:").build(),
+			0,
+			SizerFlag::AlignCenterVertical | SizerFlag::All,
+			5,
+		);
+		row.add(&controls.format, 0, SizerFlag::All, 5);
+		row.add(&controls.embed, 0, SizerFlag::All, 5);
+		sizer.add_sizer(&row, 0, SizerFlag::Expand | SizerFlag::All, 5);
+		controls
+	}
+
+	fn options(&self, languages: Vec<String>, manual: bool, automatic: bool) -> Option<SubtitleOptions> {
+		(!languages.is_empty() && (manual || automatic)).then(|| SubtitleOptions {
+			languages,
+			manual,
+			automatic,
+			format: self.format.get_string_selection().unwrap_or_else(|| SUBTITLE_FORMATS[0].to_string()),
+			embed: self.embed.get_selection() == Some(0),
+		})
+	}
+}
+
+fn remember_subtitle_languages(config_manager: &Arc<Mutex<ConfigManager>>, subtitles: Option<&SubtitleOptions>) {
+	if let Some(subtitles) = subtitles {
+		let mut cfg = config_manager.lock().expect("Config manager lock failed");
+		cfg.set_subtitle_languages(&subtitles.languages);
+		cfg.flush();
+	}
+}
+
+const SPONSORBLOCK_LABELS: [&str; 6] =
+	["Sponsor", "Intro", "Outro", "Self-promotion", "Interaction reminder", "Non-music section"];
+
+#[derive(Clone, Copy)]
+struct SponsorBlockControls {
+	actions: [Choice; 6],
+}
+
+impl SponsorBlockControls {
+	fn create(notebook: &Notebook, defaults: &SponsorBlockOptions) -> Self {
+		let panel = Panel::builder(notebook).with_style(PanelStyle::TabTraversal).build();
+		let grid = FlexGridSizer::builder(0, 2).with_vgap(5).with_hgap(10).build();
+		let actions = SPONSORBLOCK_CATEGORIES.map(|category| {
+			let choice = Choice::builder(&panel)
+				.with_choices(vec!["Keep".to_string(), "Mark as chapter".to_string(), "Remove".to_string()])
+				.build();
+			choice.set_selection(if defaults.remove.iter().any(|c| c == category) {
+				2
+			} else if defaults.mark.iter().any(|c| c == category) {
+				1
+			} else {
+				0
+			});
+			choice
+		});
+		for (label, choice) in SPONSORBLOCK_LABELS.iter().zip(&actions) {
+			grid.add(
+				&StaticText::builder(&panel).with_label(label).build(),
+				0,
+				SizerFlag::AlignCenterVertical | SizerFlag::All,
+				5,
+			);
+			grid.add(choice, 0, SizerFlag::All, 5);
+		}
+		let sizer = BoxSizer::builder(Orientation::Vertical).build();
+		sizer.add_sizer(&grid, 0, SizerFlag::All, 5);
+		panel.set_sizer(sizer, true);
+		notebook.add_page(&panel, "SponsorBlock", false, None);
+		Self { actions }
+	}
+
+	fn options(&self) -> SponsorBlockOptions {
+		let with_action = |action: u32| -> Vec<String> {
+			SPONSORBLOCK_CATEGORIES
+				.iter()
+				.zip(&self.actions)
+				.filter(|(_, choice)| choice.get_selection() == Some(action))
+				.map(|(category, _)| category.to_string())
+				.collect()
+		};
+		SponsorBlockOptions { mark: with_action(1), remove: with_action(2) }
+	}
+
+	fn remember(&self, config_manager: &Arc<Mutex<ConfigManager>>) -> Option<SponsorBlockOptions> {
+		let options = self.options();
+		let mut cfg = config_manager.lock().expect("Config manager lock failed");
+		cfg.set_sponsorblock(&options);
+		cfg.flush();
+		(!options.is_empty()).then_some(options)
+	}
+}
+
+#[derive(Clone, Copy)]
+struct MetadataControls {
+	embed_thumbnail: CheckBox,
+	thumbnail_format: Choice,
+	square_thumbnail: CheckBox,
+	embed_metadata: CheckBox,
+	write_info_json: CheckBox,
+	write_description: CheckBox,
+}
+
+impl MetadataControls {
+	fn create(notebook: &Notebook, defaults: &MetadataOptions) -> Self {
+		let panel = Panel::builder(notebook).with_style(PanelStyle::TabTraversal).build();
+		let sizer = BoxSizer::builder(Orientation::Vertical).build();
+		let controls = Self {
+			embed_thumbnail: CheckBox::builder(&panel).with_label("Embed thumbnail (--embed-thumbnail)").build(),
+			thumbnail_format: Choice::builder(&panel)
+				.with_choices(THUMBNAIL_FORMATS.map(String::from).to_vec())
+				.build(),
+			square_thumbnail: CheckBox::builder(&panel)
+				.with_label("Crop the thumbnail to a square (cover art for audio)")
+				.build(),
+			embed_metadata: CheckBox::builder(&panel).with_label("Embed metadata (--embed-metadata)").build(),
+			write_info_json: CheckBox::builder(&panel).with_label("Write .info.json (--write-info-json)").build(),
+			write_description: CheckBox::builder(&panel).with_label("Write .description (--write-description)").build(),
+		};
+		controls.embed_thumbnail.set_value(defaults.embed_thumbnail);
+		controls.thumbnail_format.set_selection(
+			defaults
+				.thumbnail_format
+				.as_deref()
+				.and_then(|f| THUMBNAIL_FORMATS.iter().position(|x| *x == f))
+				.unwrap_or_default() as u32,
+		);
+		controls.square_thumbnail.set_value(defaults.square_thumbnail);
+		controls.embed_metadata.set_value(defaults.embed_metadata);
+		controls.write_info_json.set_value(defaults.write_info_json);
+		controls.write_description.set_value(defaults.write_description);
+
+		sizer.add(&controls.embed_thumbnail, 0, SizerFlag::All, 5);
+		let thumbnail_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+		thumbnail_sizer.add(
+			&StaticText::builder(&panel).with_label("Convert to:").build(),
+			0,
+			SizerFlag::AlignCenterVertical | SizerFlag::All,
+			5,
+		);
+		thumbnail_sizer.add(&controls.thumbnail_format, 0, SizerFlag::All, 5);
+		thumbnail_sizer.add(&controls.square_thumbnail, 0, SizerFlag::AlignCenterVertical | SizerFlag::All, 5);
+		sizer.add_sizer(&thumbnail_sizer, 0, SizerFlag::Left, 20);
+		sizer.add(&controls.embed_metadata, 0, SizerFlag::All, 5);
+		sizer.add(&controls.write_info_json, 0, SizerFlag::All, 5);
+		sizer.add(&controls.write_description, 0, SizerFlag::All, 5);
+		panel.set_sizer(sizer, true);
+		notebook.add_page(&panel, "Metadata", false, None);
+
+		let set_thumbnail_enabled = move |on: bool| {
+			controls.thumbnail_format.enable(on);
+			controls.square_thumbnail.enable(on);
+		};
+		set_thumbnail_enabled(defaults.embed_thumbnail);
+		controls.embed_thumbnail.on_toggled(move |_| set_thumbnail_enabled(controls.embed_thumbnail.get_value()));
+		controls
+	}
+
+	fn remember(&self, config_manager: &Arc<Mutex<ConfigManager>>) -> Option<MetadataOptions> {
+		let options = MetadataOptions {
+			embed_thumbnail: self.embed_thumbnail.get_value(),
+			thumbnail_format: self
+				.thumbnail_format
+				.get_selection()
+				.filter(|s| *s > 0)
+				.and_then(|s| self.thumbnail_format.get_string(s)),
+			square_thumbnail: self.square_thumbnail.get_value(),
+			embed_metadata: self.embed_metadata.get_value(),
+			write_info_json: self.write_info_json.get_value(),
+			write_description: self.write_description.get_value(),
+		};
+		let mut cfg = config_manager.lock().expect("Config manager lock failed");
+		cfg.set_metadata_options(&options);
+		cfg.flush();
+		(!options.is_empty()).then_some(options)
+	}
+}
+
+#[derive(Clone, Copy)]
+struct DestinationPicker {
+	folder: Choice,
+}
+
+impl DestinationPicker {
+	fn create(
+		dialog: &Dialog,
+		sizer: &BoxSizer,
+		config_manager: &Arc<Mutex<ConfigManager>>,
+		on_change: impl Fn(Option<String>) + 'static,
+	) -> Self {
+		let (default_path, recent) = {
+			let cfg = config_manager.lock().expect("Config manager lock failed");
+			(cfg.get_download_path().unwrap_or_default(), cfg.get_recent_destinations())
+		};
+		let row = BoxSizer::builder(Orientation::Horizontal).build();
+		row.add(
+			&StaticText::builder(dialog).with_label("Save to:").build(),
+			0,
+			SizerFlag::AlignCenterVertical | SizerFlag::All,
+			5,
+		);
+		let folder = Choice::builder(dialog).build();
+		folder.append(if default_path.is_empty() { "Default folder" } else { &default_path });
+		for path in recent.iter().filter(|p| **p != default_path) {
+			folder.append(path);
+		}
+		folder.set_selection(0);
+		row.add(&folder, 1, SizerFlag::Expand | SizerFlag::All, 5);
+		let browse_btn = Button::builder(dialog).with_label("Browse...").build();
+		row.add(&browse_btn, 0, SizerFlag::All, 5);
+		sizer.add_sizer(&row, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+		let picker = Self { folder };
+		let on_change = Rc::new(on_change);
+		let on_change_choice = on_change.clone();
+		folder.on_selection_changed(move |_| on_change_choice(picker.selected()));
+		let d_browse = *dialog;
+		browse_btn.on_click(move |_| {
+			let start = picker.selected().unwrap_or_else(|| default_path.clone());
+			let dir_dialog = DirDialog::builder(&d_browse, "Select Destination Folder", &start).build();
+			if dir_dialog.show_modal() == wxdragon::id::ID_OK
+				&& let Some(path) = dir_dialog.get_path()
+			{
+				match (0..folder.get_count()).find(|i| folder.get_string(*i).as_deref() == Some(path.as_str())) {
+					Some(i) => folder.set_selection(i),
+					None => {
+						folder.append(&path);
+						folder.set_selection(folder.get_count() - 1);
+					}
+				}
+				on_change(picker.selected());
+			}
+		});
+		picker
+	}
+
+	fn selected(&self) -> Option<String> {
+		self.folder.get_selection().filter(|s| *s > 0).and_then(|s| self.folder.get_string(s))
+	}
+
+	fn remember(&self, config_manager: &Arc<Mutex<ConfigManager>>) -> Option<String> {
+		let selected = self.selected();
+		if let Some(folder) = &selected {
+			let mut cfg = config_manager.lock().expect("Config manager lock failed");
+			cfg.add_recent_destination(folder);
+			cfg.flush();
+		}
+		selected
+	}
+}
+
+pub fn show_options_dialog(
+	parent: &impl WxWidget,
+	info: &VideoInfo,
+	config_manager: &Arc<Mutex<ConfigManager>>,
+) -> Option<DownloadOptions> {
+	let presets = Rc::new(RefCell::new(config_manager.lock().expect("Config manager lock failed").get_presets()));
 	let dialog = Dialog::builder(parent, "Download Options").with_size(600, 500).build();
 	let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
 
@@ -45,6 +488,7 @@ pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<D
 		SizerFlag::All | SizerFlag::Expand,
 		10,
 	);
+	let preset_choice = create_preset_choice(&dialog, &main_sizer, &presets.borrow());
 
 	let options_sizer = BoxSizer::builder(Orientation::Vertical).build();
 	let chk_chapters = CheckBox::builder(&dialog).with_label("Add Chapters (--embed-chapters)").build();
@@ -92,6 +536,64 @@ pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<D
 	audio_panel.set_sizer(audio_sizer, true);
 	notebook.add_page(&audio_panel, "Audio", false, None);
 
+	let preferred_subtitles = config_manager.lock().expect("Config manager lock failed").get_subtitle_languages();
+	let subs_panel = Panel::builder(&notebook).with_style(PanelStyle::TabTraversal).build();
+	let subs_sizer = BoxSizer::builder(Orientation::Vertical).build();
+	let (manual_list, manual_langs) =
+		create_subtitle_list(&subs_panel, &subs_sizer, "Subtitles:", &info.subtitles, &preferred_subtitles);
+	let (auto_list, auto_langs) =
+		create_subtitle_list(&subs_panel, &subs_sizer, "Automatic captions:", &info.automatic_captions, &[]);
+	let subtitle_controls = SubtitleControls::create(&subs_panel, &subs_sizer);
+	subs_panel.set_sizer(subs_sizer, true);
+	notebook.add_page(&subs_panel, "Subtitles", false, None);
+
+	let sections_panel = Panel::builder(&notebook).with_style(PanelStyle::TabTraversal).build();
+	let sections_sizer = BoxSizer::builder(Orientation::Vertical).build();
+	sections_sizer.add(
+		&StaticText::builder(&sections_panel)
+			.with_label("Time ranges to download (one per line, e.g. 1:02:00-1:04:00):")
+			.build(),
+		0,
+		SizerFlag::All,
+		5,
+	);
+	let ranges_text = TextCtrl::builder(&sections_panel).with_style(TextCtrlStyle::MultiLine).build();
+	sections_sizer.add(&ranges_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	sections_sizer.add(&StaticText::builder(&sections_panel).with_label("Chapters:").build(), 0, SizerFlag::All, 5);
+	let chapter_list = ListCtrl::builder(&sections_panel).with_style(ListCtrlStyle::Report).build();
+	chapter_list.insert_column(0, "Start", ListColumnFormat::Left, 80);
+	chapter_list.insert_column(1, "End", ListColumnFormat::Left, 80);
+	chapter_list.insert_column(2, "Title", ListColumnFormat::Left, 360);
+	for (i, chapter) in info.chapters.iter().enumerate() {
+		let row = i as i64;
+		chapter_list.insert_item(row, &download_options::format_position(chapter.start_time.trunc()), None);
+		chapter_list.set_item_text_by_column(row, 1, &download_options::format_position(chapter.end_time.trunc()));
+		chapter_list.set_item_text_by_column(row, 2, chapter.title.as_deref().unwrap_or_default());
+	}
+	sections_sizer.add(&chapter_list, 2, SizerFlag::Expand | SizerFlag::All, 5);
+	let chk_precise =
+		CheckBox::builder(&sections_panel).with_label("Precise cuts (--force-keyframes-at-cuts, re-encodes)").build();
+	sections_sizer.add(&chk_precise, 0, SizerFlag::All, 5);
+	sections_panel.set_sizer(sections_sizer, true);
+	notebook.add_page(&sections_panel, "Sections", false, None);
+	let sponsorblock_controls = SponsorBlockControls::create(
+		&notebook,
+		&config_manager.lock().expect("Config manager lock failed").get_sponsorblock(),
+	);
+	let metadata_controls = MetadataControls::create(
+		&notebook,
+		&config_manager.lock().expect("Config manager lock failed").get_metadata_options(),
+	);
+
+	let format_panels = [video_panel, audio_panel];
+	preset_choice.on_selection_changed(move |_| {
+		format_panels.iter().for_each(|p| p.enable(preset_choice.get_selection().unwrap_or(0) == 0))
+	});
+	let audio = AudioControls::create(&dialog, &main_sizer);
+	create_save_preset_button(&dialog, &main_sizer, config_manager, &presets, audio, preset_choice, format_panels);
+
+	let destination_picker = DestinationPicker::create(&dialog, &main_sizer, config_manager, |_| {});
+
 	let btn_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 	let ok_btn = Button::builder(&dialog).with_label("Download").build();
 	let cancel_btn = Button::builder(&dialog).with_label("Cancel").build();
@@ -101,7 +603,12 @@ pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<D
 	main_sizer.add_sizer(&btn_sizer, 0, SizerFlag::Expand | SizerFlag::All, 10);
 
 	let d_ok = dialog;
-	ok_btn.on_click(move |_| d_ok.end_modal(RET_OK));
+	ok_btn.on_click(move |_| match download_options::parse_time_ranges(&ranges_text.get_value()) {
+		Ok(_) => d_ok.end_modal(RET_OK),
+		Err(e) => {
+			let _ = MessageDialog::builder(&d_ok, &e, "Invalid Time Range").build().show_modal();
+		}
+	});
 	let d_cancel = dialog;
 	cancel_btn.on_click(move |_| d_cancel.end_modal(RET_CANCEL));
 
@@ -109,6 +616,44 @@ pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<D
 	dialog.centre();
 
 	(dialog.show_modal() == RET_OK).then(|| {
+		let mut ranges = download_options::parse_time_ranges(&ranges_text.get_value()).unwrap_or_default();
+		ranges.extend(
+			selected_items(&chapter_list)
+				.into_iter()
+				.filter_map(|i| info.chapters.get(i))
+				.map(|c| TimeRange { start: c.start_time, end: c.end_time }),
+		);
+		let sections = (!ranges.is_empty()).then(|| DownloadSections { ranges, precise: chk_precise.get_value() });
+		let sponsorblock = sponsorblock_controls.remember(config_manager);
+		let metadata = metadata_controls.remember(config_manager);
+		let destination = destination_picker.remember(config_manager);
+
+		let manual: Vec<String> =
+			selected_items(&manual_list).into_iter().filter_map(|i| manual_langs.get(i).cloned()).collect();
+		let automatic: Vec<String> =
+			selected_items(&auto_list).into_iter().filter_map(|i| auto_langs.get(i).cloned()).collect();
+		let mut languages = manual.clone();
+		languages.extend(automatic.iter().filter(|l| !manual.contains(l)).cloned());
+		let subtitles = subtitle_controls.options(languages, !manual.is_empty(), !automatic.is_empty());
+		remember_subtitle_languages(config_manager, subtitles.as_ref());
+
+		if let Some(preset) = selected_preset(&preset_choice, &presets.borrow()) {
+			let options = DownloadOptions::from_preset(
+				preset,
+				DownloadMode::Single { video_format: None, audio_formats: Vec::new() },
+			);
+			return DownloadOptions {
+				add_chapters: chk_chapters.get_value(),
+				multi_audio: chk_multi_audio.get_value(),
+				extract_audio: audio.extraction().or(options.extract_audio.clone()),
+				subtitles,
+				sections,
+				sponsorblock,
+				metadata,
+				destination,
+				..options
+			};
+		}
 		let item = video_list.get_next_item(-1, ListNextItemFlag::All, ListItemState::Selected);
 		let video_format = (item != -1).then(|| sorted_video.get(item as usize).map(|f| f.format_id.clone())).flatten();
 
@@ -130,11 +675,62 @@ pub fn show_options_dialog(parent: &impl WxWidget, info: &VideoInfo) -> Option<D
 			add_chapters: chk_chapters.get_value(),
 			multi_audio: chk_multi_audio.get_value(),
 			preferred_languages,
+			preset: None,
+			extract_audio: audio.extraction(),
+			subtitles,
+			sections,
+			sponsorblock,
+			metadata,
+			destination,
 		}
 	})
 }
 
-pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Option<DownloadOptions> {
+fn playlist_destination(
+	config_manager: &Arc<Mutex<ConfigManager>>,
+	selected: Option<String>,
+	subfolder: bool,
+	videos: &[VideoInfo],
+) -> Option<String> {
+	if !subfolder {
+		return selected;
+	}
+	let base = selected.clone().unwrap_or_else(|| {
+		config_manager.lock().expect("Config manager lock failed").get_download_path().unwrap_or_default()
+	});
+	destination::playlist_folder(&base, videos).or(selected)
+}
+
+fn load_archive(config_manager: &Arc<Mutex<ConfigManager>>, destination: Option<&str>) -> HashSet<String> {
+	let cfg = config_manager.lock().expect("Config manager lock failed");
+	let download_path = cfg.get_download_path();
+	cfg.archive_path(destination.or(download_path.as_deref())).map(|p| download_archive::load(&p)).unwrap_or_default()
+}
+
+fn mark_archived(list: &ListCtrl, header: &StaticText, videos: &[VideoInfo], archive: &HashSet<String>) {
+	let mut archived_count = 0;
+	for (i, v) in videos.iter().enumerate() {
+		if download_archive::contains(archive, v) {
+			archived_count += 1;
+			list.set_item_text(i as i64, &format!("{}: [Downloaded] {}", i + 1, v.title));
+			list.set_item_state(i as i64, ListItemState::None, ListItemState::Selected);
+		} else {
+			list.set_item_text(i as i64, &format!("{}: {}", i + 1, v.title));
+			list.set_item_state(i as i64, ListItemState::Selected, ListItemState::Selected);
+		}
+	}
+	header.set_label(&if archived_count > 0 {
+		format!("Found {} videos, {} already downloaded. Select items to download:", videos.len(), archived_count)
+	} else {
+		format!("Found {} videos. Select items to download:", videos.len())
+	});
+}
+
+pub fn show_playlist_dialog(
+	parent: &impl WxWidget,
+	videos: &[VideoInfo],
+	config_manager: &Arc<Mutex<ConfigManager>>,
+) -> Option<DownloadOptions> {
 	if videos.is_empty() {
 		None
 	} else {
@@ -142,20 +738,13 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 		let dialog = Dialog::builder(parent, "Playlist Download Options").with_size(800, 700).build();
 		let main_sizer = BoxSizer::builder(Orientation::Vertical).build();
 
-		main_sizer.add(
-			&StaticText::builder(&dialog)
-				.with_label(&format!("Found {} videos. Select items to download:", videos.len()))
-				.build(),
-			0,
-			SizerFlag::All | SizerFlag::Expand,
-			5,
-		);
+		let header = StaticText::builder(&dialog).build();
+		main_sizer.add(&header, 0, SizerFlag::All | SizerFlag::Expand, 5);
 
 		let list_ctrl = ListCtrl::builder(&dialog).with_style(ListCtrlStyle::Report).build();
 		list_ctrl.insert_column(0, "Video", ListColumnFormat::Left, 550);
 		for (i, v) in videos.iter().enumerate() {
-			list_ctrl.insert_item(i as i64, &format!("{}: {}", i + 1, v.title), None);
-			list_ctrl.set_item_state(i as i64, ListItemState::Selected, ListItemState::Selected);
+			list_ctrl.insert_item(i as i64, &v.title, None);
 		}
 		main_sizer.add(&list_ctrl, 2, SizerFlag::Expand | SizerFlag::All, 5);
 
@@ -172,6 +761,8 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 			SizerFlag::All | SizerFlag::Top,
 			10,
 		);
+		let presets = Rc::new(RefCell::new(config_manager.lock().expect("Config manager lock failed").get_presets()));
+		let preset_choice = create_preset_choice(&dialog, &main_sizer, &presets.borrow());
 
 		let notebook = Notebook::builder(&dialog).build();
 		main_sizer.add(&notebook, 3, SizerFlag::Expand | SizerFlag::All, 5);
@@ -211,6 +802,56 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 		audio_panel.set_sizer(audio_sizer, true);
 		notebook.add_page(&audio_panel, "Audio", false, None);
 
+		let subs_panel = Panel::builder(&notebook).with_style(PanelStyle::TabTraversal).build();
+		let subs_sizer = BoxSizer::builder(Orientation::Vertical).build();
+		subs_sizer.add(
+			&StaticText::builder(&subs_panel)
+				.with_label("Preferred languages for every video (comma separated, e.g. en,de,pt.*):")
+				.build(),
+			0,
+			SizerFlag::All,
+			5,
+		);
+		let subs_langs_text = TextCtrl::builder(&subs_panel)
+			.with_value(&config_manager.lock().expect("Config manager lock failed").get_subtitle_languages().join(","))
+			.build();
+		subs_sizer.add(&subs_langs_text, 0, SizerFlag::Expand | SizerFlag::All, 5);
+		let available: Vec<&str> =
+			ref_video.subtitles.keys().map(String::as_str).filter(|l| *l != "live_chat").collect();
+		if !available.is_empty() {
+			subs_sizer.add(
+				&StaticText::builder(&subs_panel)
+					.with_label(&format!("Subtitles in the first video: {}", available.join(", ")))
+					.build(),
+				0,
+				SizerFlag::All,
+				5,
+			);
+		}
+		let chk_manual_subs = CheckBox::builder(&subs_panel).with_label("Subtitles").build();
+		chk_manual_subs.set_value(true);
+		let chk_auto_subs = CheckBox::builder(&subs_panel).with_label("Automatic captions").build();
+		subs_sizer.add(&chk_manual_subs, 0, SizerFlag::All, 5);
+		subs_sizer.add(&chk_auto_subs, 0, SizerFlag::All, 5);
+		let subtitle_controls = SubtitleControls::create(&subs_panel, &subs_sizer);
+		subs_panel.set_sizer(subs_sizer, true);
+		notebook.add_page(&subs_panel, "Subtitles", false, None);
+		let sponsorblock_controls = SponsorBlockControls::create(
+			&notebook,
+			&config_manager.lock().expect("Config manager lock failed").get_sponsorblock(),
+		);
+		let metadata_controls = MetadataControls::create(
+			&notebook,
+			&config_manager.lock().expect("Config manager lock failed").get_metadata_options(),
+		);
+
+		let format_panels = [video_panel, audio_panel];
+		preset_choice.on_selection_changed(move |_| {
+			format_panels.iter().for_each(|p| p.enable(preset_choice.get_selection().unwrap_or(0) == 0))
+		});
+		let audio = AudioControls::create(&dialog, &main_sizer);
+		create_save_preset_button(&dialog, &main_sizer, config_manager, &presets, audio, preset_choice, format_panels);
+
 		let options_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 		let chk_chapters = CheckBox::builder(&dialog).with_label("Add Chapters").build();
 		chk_chapters.set_value(true);
@@ -219,6 +860,33 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 		options_sizer.add(&chk_multi_audio, 0, SizerFlag::All, 5);
 		main_sizer.add_sizer(&options_sizer, 0, SizerFlag::All, 5);
 
+		let playlist_title = destination::playlist_title(videos);
+		let chk_subfolder = CheckBox::builder(&dialog)
+			.with_label(&format!(
+				"Save into a subfolder named after the playlist ({})",
+				playlist_title.map(destination::sanitize_folder_name).unwrap_or_default()
+			))
+			.build();
+		chk_subfolder.set_value(
+			playlist_title.is_some()
+				&& config_manager.lock().expect("Config manager lock failed").get_playlist_subfolder(),
+		);
+		chk_subfolder.enable(playlist_title.is_some());
+		let refresh_archive = {
+			let (cfg, videos) = (config_manager.clone(), videos.to_vec());
+			move |selected: Option<String>, subfolder: bool| {
+				let destination = playlist_destination(&cfg, selected, subfolder, &videos);
+				mark_archived(&list_ctrl, &header, &videos, &load_archive(&cfg, destination.as_deref()));
+			}
+		};
+		let refresh_picker = refresh_archive.clone();
+		let destination_picker = DestinationPicker::create(&dialog, &main_sizer, config_manager, move |selected| {
+			refresh_picker(selected, chk_subfolder.get_value())
+		});
+		main_sizer.add(&chk_subfolder, 0, SizerFlag::All, 5);
+		refresh_archive(None, chk_subfolder.get_value());
+		chk_subfolder.on_toggled(move |_| refresh_archive(destination_picker.selected(), chk_subfolder.get_value()));
+
 		let btn_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 		let ok_btn = Button::builder(&dialog).with_label("Download").build();
 		let cancel_btn = Button::builder(&dialog).with_label("Cancel").build();
@@ -258,6 +926,42 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 				item = list_ctrl.get_next_item(item as i64, ListNextItemFlag::All, ListItemState::Selected);
 			}
 
+			let languages: Vec<String> = subs_langs_text
+				.get_value()
+				.split(',')
+				.map(|l| l.trim().to_string())
+				.filter(|l| !l.is_empty())
+				.collect();
+			let subtitles =
+				subtitle_controls.options(languages, chk_manual_subs.get_value(), chk_auto_subs.get_value());
+			remember_subtitle_languages(config_manager, subtitles.as_ref());
+			let sponsorblock = sponsorblock_controls.remember(config_manager);
+			let metadata = metadata_controls.remember(config_manager);
+			let destination = playlist_destination(
+				config_manager,
+				destination_picker.remember(config_manager),
+				chk_subfolder.get_value(),
+				videos,
+			);
+
+			if let Some(preset) = selected_preset(&preset_choice, &presets.borrow()) {
+				let options = DownloadOptions::from_preset(
+					preset,
+					DownloadMode::Playlist { indices, video_format: None, audio_formats: Vec::new() },
+				);
+				return DownloadOptions {
+					add_chapters: chk_chapters.get_value(),
+					multi_audio: chk_multi_audio.get_value(),
+					extract_audio: audio.extraction().or(options.extract_audio.clone()),
+					subtitles,
+					sections: None,
+					sponsorblock,
+					metadata,
+					destination,
+					..options
+				};
+			}
+
 			let v_item = video_list.get_next_item(-1, ListNextItemFlag::All, ListItemState::Selected);
 			let video_format =
 				(v_item != -1).then(|| sorted_video.get(v_item as usize).map(|f| f.format_id.clone())).flatten();
@@ -280,6 +984,13 @@ pub fn show_playlist_dialog(parent: &impl WxWidget, videos: &[VideoInfo]) -> Opt
 				add_chapters: chk_chapters.get_value(),
 				multi_audio: chk_multi_audio.get_value(),
 				preferred_languages,
+				preset: None,
+				extract_audio: audio.extraction(),
+				subtitles,
+				sections: None,
+				sponsorblock,
+				metadata,
+				destination,
 			}
 		})
 	}
@@ -0,0 +1,247 @@
+use crate::{
+	download_options::{DownloadMode, DownloadOptions},
+	video_info::VideoInfo,
+};
+
+pub const DEFAULT_TEMPLATE: &str = "%(title)s [%(id)s].%(ext)s";
+const SECTION_SUFFIX: &str = " %(section_start)d-%(section_end)d";
+const NA_PLACEHOLDER: &str = "NA";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateKind {
+	Single,
+	Playlist,
+	Audio,
+}
+
+impl TemplateKind {
+	pub const ALL: [TemplateKind; 3] = [TemplateKind::Single, TemplateKind::Playlist, TemplateKind::Audio];
+
+	pub fn for_options(options: &DownloadOptions) -> Self {
+		if options.extract_audio.is_some() {
+			TemplateKind::Audio
+		} else {
+			match options.mode {
+				DownloadMode::Playlist { .. } => TemplateKind::Playlist,
+				DownloadMode::Single { .. } => TemplateKind::Single,
+			}
+		}
+	}
+
+	pub fn config_key(self) -> &'static str {
+		match self {
+			TemplateKind::Single => "output_template_single",
+			TemplateKind::Playlist => "output_template_playlist",
+			TemplateKind::Audio => "output_template_audio",
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			TemplateKind::Single => "Single Videos:",
+			TemplateKind::Playlist => "Playlists:",
+			TemplateKind::Audio => "Audio Only:",
+		}
+	}
+}
+
+pub fn sample_info() -> VideoInfo {
+	serde_json::from_value(serde_json::json!({
+		"id": "dQw4w9WgXcQ",
+		"title": "Sample Video Title",
+		"uploader": "Sample Channel",
+		"upload_date": "20240131",
+		"ext": "mp4",
+		"duration": 212.0,
+		"playlist_title": "Sample Playlist",
+		"playlist_index": 1,
+		"playlist_count": 12,
+	}))
+	.expect("Sample video info is valid")
+}
+
+pub fn with_section_suffix(template: &str) -> String {
+	match template.strip_suffix(".%(ext)s") {
+		Some(stem) => format!("{}{}.%(ext)s", stem, SECTION_SUFFIX),
+		None => format!("{}{}", template, SECTION_SUFFIX),
+	}
+}
+
+fn field(info: &VideoInfo, name: &str) -> Option<String> {
+	match name {
+		"id" => Some(info.id.clone()),
+		"title" | "fulltitle" => Some(info.title.clone()),
+		"uploader" => info.uploader.clone(),
+		"channel" => info.channel.clone().or_else(|| info.uploader.clone()),
+		"upload_date" => info.upload_date.clone(),
+		"ext" => info.ext.clone(),
+		"duration" => info.duration.map(|d| d.to_string()),
+		"view_count" => info.view_count.map(|v| v.to_string()),
+		"webpage_url" => info.webpage_url.clone(),
+		"playlist" | "playlist_title" => info.playlist_title.clone(),
+		"playlist_index" => info.playlist_index.map(|i| i.to_string()),
+		"playlist_count" | "n_entries" => info.playlist_count.map(|c| c.to_string()),
+		"extractor" | "extractor_key" => info.extractor_key.clone().or_else(|| info.ie_key.clone()),
+		_ => None,
+	}
+}
+
+fn format_date(date: &str, format: &str) -> Option<String> {
+	if date.len() != 8 || !date.bytes().all(|b| b.is_ascii_digit()) {
+		return None;
+	}
+	let (year, month, day) = (&date[..4], &date[4..6], &date[6..]);
+	let mut out = String::new();
+	let mut chars = format.chars();
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			out.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('Y') => out.push_str(year),
+			Some('y') => out.push_str(&year[2..]),
+			Some('m') => out.push_str(month),
+			Some('d') => out.push_str(day),
+			Some(other) => {
+				out.push('%');
+				out.push(other);
+			}
+			None => out.push('%'),
+		}
+	}
+	Some(out)
+}
+
+fn lookup(info: &VideoInfo, key: &str) -> Option<String> {
+	let (key, default) = match key.split_once('|') {
+		Some((key, default)) => (key, Some(default)),
+		None => (key, None),
+	};
+	key.split(',')
+		.find_map(|alternative| match alternative.split_once('>') {
+			Some((name, date_format)) => field(info, name.trim()).and_then(|d| format_date(&d, date_format)),
+			None => field(info, alternative.trim()),
+		})
+		.map(|v| v.replace(['/', '\\'], "⧸"))
+		.or(default.map(String::from))
+}
+
+fn convert(value: Option<String>, spec: &str, conversion: char) -> String {
+	let left = spec.contains('-');
+	let zero = spec.trim_start_matches('-').starts_with('0');
+	let width: usize = spec.trim_start_matches(['-', '0']).split('.').next().and_then(|w| w.parse().ok()).unwrap_or(0);
+	let text = match (value, conversion) {
+		(Some(v), 'd' | 'i') => v.parse::<f64>().map(|n| (n as i64).to_string()).unwrap_or(v),
+		(Some(v), _) => v,
+		(None, _) => NA_PLACEHOLDER.to_string(),
+	};
+	match (left, zero && matches!(conversion, 'd' | 'i')) {
+		(true, _) => format!("{:<width$}", text, width = width),
+		(false, true) => format!("{:0>width$}", text, width = width),
+		(false, false) => format!("{:>width$}", text, width = width),
+	}
+}
+
+pub fn render(template: &str, info: &VideoInfo) -> String {
+	let mut out = String::new();
+	let mut rest = template;
+	while let Some(pos) = rest.find('%') {
+		out.push_str(&rest[..pos]);
+		rest = &rest[pos + 1..];
+		if let Some(after) = rest.strip_prefix('%') {
+			out.push('%');
+			rest = after;
+			continue;
+		}
+		let Some((key, after)) = rest.strip_prefix('(').and_then(|r| r.split_once(')')) else {
+			out.push('%');
+			continue;
+		};
+		let spec_len = after.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(after.len());
+		let (spec, after) = after.split_at(spec_len);
+		let mut conversion = after.chars();
+		out.push_str(&convert(lookup(info, key), spec, conversion.next().unwrap_or('s')));
+		rest = conversion.as_str();
+	}
+	out.push_str(rest);
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::download_options::AudioExtraction;
+
+	#[test]
+	fn default_template() {
+		assert_eq!(render(DEFAULT_TEMPLATE, &sample_info()), "Sample Video Title [dQw4w9WgXcQ].mp4");
+	}
+
+	#[test]
+	fn numeric_width_and_padding() {
+		let info = sample_info();
+		assert_eq!(render("%(playlist_index)03d - %(title)s", &info), "001 - Sample Video Title");
+		assert_eq!(render("[%(playlist_index)3d]", &info), "[  1]");
+		assert_eq!(render("[%(id)-13s]", &info), "[dQw4w9WgXcQ  ]");
+		assert_eq!(render("%(duration)d", &info), "212");
+	}
+
+	#[test]
+	fn alternatives_and_defaults() {
+		let info = sample_info();
+		assert_eq!(render("%(channel,uploader)s", &info), "Sample Channel");
+		assert_eq!(render("%(missing,uploader)s", &info), "Sample Channel");
+		assert_eq!(render("%(missing|Unknown)s", &info), "Unknown");
+		assert_eq!(render("%(title|Unknown)s", &info), "Sample Video Title");
+	}
+
+	#[test]
+	fn unknown_fields_render_as_na() {
+		assert_eq!(render("%(missing)s-%(missing)d", &sample_info()), "NA-NA");
+	}
+
+	#[test]
+	fn date_formatting() {
+		let info = sample_info();
+		assert_eq!(render("%(upload_date>%Y-%m-%d)s", &info), "2024-01-31");
+		assert_eq!(render("%(upload_date>%d.%m.%y)s", &info), "31.01.24");
+		let undated = VideoInfo { upload_date: Some("recently".into()), ..info };
+		assert_eq!(render("%(upload_date>%Y|undated)s", &undated), "undated");
+	}
+
+	#[test]
+	fn escapes_and_malformed_keys() {
+		let info = sample_info();
+		assert_eq!(render("100%% %(id)s", &info), "100% dQw4w9WgXcQ");
+		assert_eq!(render("50% off", &info), "50% off");
+		assert_eq!(render("%(id", &info), "%(id");
+	}
+
+	#[test]
+	fn path_separators_are_replaced() {
+		let info = VideoInfo { title: "AC/DC \\ Live".into(), ..sample_info() };
+		assert_eq!(render("%(title)s", &info), "AC⧸DC ⧸ Live");
+		assert_eq!(render("%(playlist)s/%(title)s", &info), "Sample Playlist/AC⧸DC ⧸ Live");
+	}
+
+	#[test]
+	fn section_suffix() {
+		assert_eq!(
+			with_section_suffix(DEFAULT_TEMPLATE),
+			"%(title)s [%(id)s] %(section_start)d-%(section_end)d.%(ext)s"
+		);
+		assert_eq!(with_section_suffix("%(title)s"), "%(title)s %(section_start)d-%(section_end)d");
+	}
+
+	#[test]
+	fn template_kind_follows_options() {
+		let single = DownloadMode::Single { video_format: None, audio_formats: Vec::new() };
+		let playlist = DownloadMode::Playlist { indices: vec![1], video_format: None, audio_formats: Vec::new() };
+		let audio = AudioExtraction { codec: "mp3".into(), quality: "192K".into() };
+		assert_eq!(TemplateKind::for_options(&DownloadOptions::for_mode(single)), TemplateKind::Single);
+		assert_eq!(TemplateKind::for_options(&DownloadOptions::for_mode(playlist.clone())), TemplateKind::Playlist);
+		let audio_only = DownloadOptions { extract_audio: Some(audio), ..DownloadOptions::for_mode(playlist) };
+		assert_eq!(TemplateKind::for_options(&audio_only), TemplateKind::Audio);
+	}
+}
@@ -0,0 +1,203 @@
+use std::{
+	env,
+	ffi::OsStr,
+	io,
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+#[cfg(windows)]
+pub const EXECUTABLE_WILDCARD: &str = "Executables (*.exe)|*.exe|All Files (*.*)|*.*";
+#[cfg(not(windows))]
+pub const EXECUTABLE_WILDCARD: &str = "All Files (*)|*";
+
+pub fn command(program: impl AsRef<OsStr>) -> Command {
+	#[allow(unused_mut)]
+	let mut cmd = Command::new(program);
+	#[cfg(windows)]
+	{
+		use std::os::windows::process::CommandExt;
+		cmd.creation_flags(CREATE_NO_WINDOW);
+	}
+	cmd
+}
+
+#[cfg(windows)]
+pub fn expand_env_vars(arg: &str) -> String {
+	let mut expanded = arg.to_string();
+	let mut start = 0;
+	while let Some(pos) = expanded[start..].find('%') {
+		let actual_pos = start + pos;
+		if let Some(end_pos) = expanded[actual_pos + 1..].find('%') {
+			let actual_end = actual_pos + 1 + end_pos;
+			let var_name = &expanded[actual_pos + 1..actual_end];
+			if let Ok(val) = std::env::var(var_name) {
+				expanded.replace_range(actual_pos..actual_end + 1, &val);
+				start = actual_pos + val.len();
+			} else {
+				start = actual_end + 1;
+			}
+		} else {
+			break;
+		}
+	}
+	expanded.replace("/", "\\")
+}
+
+#[cfg(not(windows))]
+pub fn expand_env_vars(arg: &str) -> String {
+	let mut expanded = String::with_capacity(arg.len());
+	let mut rest = arg;
+	while let Some(pos) = rest.find('$') {
+		expanded.push_str(&rest[..pos]);
+		let after = &rest[pos + 1..];
+		let (name, consumed) = match after.strip_prefix('{') {
+			Some(braced) => braced.find('}').map(|end| (&braced[..end], end + 2)).unwrap_or(("", 0)),
+			None => {
+				let end = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+				(&after[..end], end)
+			}
+		};
+		match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+			Some(val) => expanded.push_str(&val),
+			None => expanded.push_str(&rest[pos..pos + 1 + consumed]),
+		}
+		rest = &after[consumed..];
+	}
+	expanded.push_str(rest);
+	expanded
+}
+
+#[cfg(unix)]
+pub fn isolate_process_group(cmd: &mut Command) {
+	use std::os::unix::process::CommandExt;
+	cmd.process_group(0);
+}
+
+#[cfg(not(unix))]
+pub fn isolate_process_group(_cmd: &mut Command) {}
+
+#[cfg(unix)]
+pub fn interrupt_tree(pid: u32) -> bool { unsafe { libc::kill(-(pid as libc::pid_t), libc::SIGINT) == 0 } }
+
+// Without /F, taskkill asks each process in the tree to close instead of terminating it.
+#[cfg(windows)]
+pub fn interrupt_tree(pid: u32) -> bool {
+	command("taskkill")
+		.arg("/PID")
+		.arg(pid.to_string())
+		.arg("/T")
+		.stdout(std::process::Stdio::null())
+		.stderr(std::process::Stdio::null())
+		.status()
+		.is_ok_and(|s| s.success())
+}
+
+#[cfg(unix)]
+pub fn kill_tree(pid: u32) {
+	unsafe {
+		libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+	}
+}
+
+#[cfg(windows)]
+pub fn kill_tree(pid: u32) {
+	let _ = command("taskkill")
+		.arg("/PID")
+		.arg(pid.to_string())
+		.arg("/T")
+		.arg("/F")
+		.stdout(std::process::Stdio::null())
+		.stderr(std::process::Stdio::null())
+		.status();
+}
+
+#[cfg(unix)]
+pub fn is_tree_alive(pid: u32) -> bool { unsafe { libc::kill(-(pid as libc::pid_t), 0) == 0 } }
+
+// taskkill /F only returns once the processes in the tree have been terminated.
+#[cfg(windows)]
+pub fn is_tree_alive(_pid: u32) -> bool { false }
+
+pub fn open_path(path: &Path) -> io::Result<()> {
+	let opener = if cfg!(windows) {
+		"explorer"
+	} else if cfg!(target_os = "macos") {
+		"open"
+	} else {
+		"xdg-open"
+	};
+	command(opener).arg(path).spawn().map(|_| ())
+}
+
+pub fn exe_name(name: &str) -> String { if cfg!(windows) { format!("{}.exe", name) } else { name.to_string() } }
+
+#[cfg(unix)]
+pub fn set_executable(path: &Path) -> io::Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	let mut perms = std::fs::metadata(path)?.permissions();
+	perms.set_mode(perms.mode() | 0o755);
+	std::fs::set_permissions(path, perms)
+}
+
+#[cfg(not(unix))]
+pub fn set_executable(_path: &Path) -> io::Result<()> { Ok(()) }
+
+pub fn find_executable(path: &str) -> Option<PathBuf> {
+	let candidates = |p: PathBuf| {
+		let exe = p.with_file_name(exe_name(&p.file_name()?.to_string_lossy()));
+		[p, exe].into_iter().find(|c| c.is_file())
+	};
+	let p = Path::new(path);
+	if p.is_absolute() || p.parent().is_some_and(|parent| !parent.as_os_str().is_empty()) {
+		candidates(p.to_path_buf())
+	} else {
+		env::split_paths(&env::var_os("PATH")?).find_map(|dir| candidates(dir.join(path)))
+	}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+	use super::*;
+
+	fn set_test_vars() {
+		// Only std reads the environment in these tests, and std serializes its own access to it.
+		unsafe {
+			env::set_var("TUBEX_TEST_DIR", "/home/user/Videos");
+			env::set_var("TUBEX_TEST_NAME", "clip");
+			env::remove_var("TUBEX_TEST_UNSET");
+		}
+	}
+
+	#[test]
+	fn expands_plain_variables() {
+		set_test_vars();
+		assert_eq!(expand_env_vars("$TUBEX_TEST_DIR/out"), "/home/user/Videos/out");
+		assert_eq!(expand_env_vars("$TUBEX_TEST_DIR.$TUBEX_TEST_NAME"), "/home/user/Videos.clip");
+		assert_eq!(expand_env_vars("$TUBEX_TEST_UNSET/out"), "$TUBEX_TEST_UNSET/out");
+		assert_eq!(expand_env_vars("no variables"), "no variables");
+	}
+
+	#[test]
+	fn expands_braced_variables() {
+		set_test_vars();
+		assert_eq!(expand_env_vars("${TUBEX_TEST_NAME}_1.mp4"), "clip_1.mp4");
+		assert_eq!(expand_env_vars("${TUBEX_TEST_DIR}/${TUBEX_TEST_NAME}"), "/home/user/Videos/clip");
+		assert_eq!(expand_env_vars("${TUBEX_TEST_UNSET}/out"), "${TUBEX_TEST_UNSET}/out");
+		assert_eq!(expand_env_vars("${}"), "${}");
+	}
+
+	#[test]
+	fn keeps_incomplete_references() {
+		set_test_vars();
+		assert_eq!(expand_env_vars("${TUBEX_TEST_DIR"), "${TUBEX_TEST_DIR");
+		assert_eq!(expand_env_vars("${TUBEX_TEST_DIR/$TUBEX_TEST_NAME"), "${TUBEX_TEST_DIR/clip");
+		assert_eq!(expand_env_vars("cost: 5$"), "cost: 5$");
+		assert_eq!(expand_env_vars("$"), "$");
+		assert_eq!(expand_env_vars("$$TUBEX_TEST_NAME"), "$clip");
+		assert_eq!(expand_env_vars("$-x"), "$-x");
+	}
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::download_options::AudioExtraction;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FormatPreset {
+	pub name: String,
+	pub format: String,
+	pub sort: String,
+	#[serde(default)]
+	pub extract_audio: Option<AudioExtraction>,
+}
+
+impl FormatPreset {
+	pub fn new(name: &str, format: &str, sort: &str) -> Self {
+		Self {
+			name: name.trim().to_string(),
+			format: format.trim().to_string(),
+			sort: sort.trim().to_string(),
+			extract_audio: None,
+		}
+	}
+
+	pub fn defaults() -> Vec<Self> {
+		vec![
+			Self::new("Best 1080p H.264 + AAC", "bv*+ba/b", "res:1080,vcodec:h264,acodec:aac"),
+			Self::new("Audio only Opus", "ba/b", "acodec:opus"),
+			Self {
+				extract_audio: Some(AudioExtraction { codec: "mp3".into(), quality: "192K".into() }),
+				..Self::new("Audio as MP3 192K", "ba/b", "")
+			},
+			Self::new(
+				"Smallest under 500 MB",
+				"(bv*+ba/b)[filesize<500M]/(bv*+ba/b)[filesize_approx<500M]/wv*+wa/w",
+				"+size,+br",
+			),
+		]
+	}
+
+	pub fn to_config_value(&self) -> String {
+		match &self.extract_audio {
+			Some(audio) => format!("{} | {} | {} | {}", self.name, self.format, self.sort, audio.to_config_value()),
+			None => format!("{} | {} | {}", self.name, self.format, self.sort),
+		}
+	}
+
+	// A stray '|' in a hand-edited value drops the preset instead of shifting its fields.
+	pub fn from_config_value(value: &str) -> Option<Self> {
+		let parts: Vec<&str> = value.split('|').collect();
+		let (preset, audio) = match parts[..] {
+			[name, format, sort] => (Self::new(name, format, sort), None),
+			[name, format, sort, audio] => {
+				(Self::new(name, format, sort), Some(AudioExtraction::from_config_value(audio)?))
+			}
+			_ => return None,
+		};
+		(!preset.name.is_empty() && !(preset.format.is_empty() && preset.sort.is_empty()))
+			.then_some(Self { extract_audio: audio, ..preset })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_config_values() {
+		assert_eq!(
+			FormatPreset::from_config_value(" 720p | bv*[height<=720]+ba/b | res:720 "),
+			Some(FormatPreset::new("720p", "bv*[height<=720]+ba/b", "res:720"))
+		);
+		assert_eq!(
+			FormatPreset::from_config_value("Sorted only | | +size"),
+			Some(FormatPreset::new("Sorted only", "", "+size"))
+		);
+		let audio = FormatPreset::from_config_value("MP3 | ba/b | | mp3:192K").unwrap();
+		assert_eq!(audio.extract_audio, Some(AudioExtraction { codec: "mp3".into(), quality: "192K".into() }));
+	}
+
+	#[test]
+	fn rejects_malformed_values() {
+		for value in [
+			"",
+			"Only a name",
+			"Name | ba/b",
+			"Name | ba/b | +size | mp3:192K | extra",
+			"Name | bv*+ba/b | res:1080 | best",
+			"Name | ba/b | | wav-ish:0",
+			" | ba/b | +size",
+			"Name | | ",
+		] {
+			assert_eq!(FormatPreset::from_config_value(value), None, "{:?}", value);
+		}
+	}
+
+	#[test]
+	fn round_trips_through_config_values() {
+		for preset in FormatPreset::defaults() {
+			assert_eq!(FormatPreset::from_config_value(&preset.to_config_value()), Some(preset));
+		}
+	}
+
+	#[test]
+	fn default_presets() {
+		let presets = FormatPreset::defaults();
+		let names: Vec<&str> = presets.iter().map(|p| p.name.as_str()).collect();
+		assert_eq!(names, ["Best 1080p H.264 + AAC", "Audio only Opus", "Audio as MP3 192K", "Smallest under 500 MB"]);
+		assert!(presets.iter().all(|p| !p.format.is_empty() && !p.name.contains('|') && !p.format.contains('|')));
+		assert_eq!(presets.iter().filter(|p| p.extract_audio.is_some()).count(), 1);
+		assert_eq!(presets[0].to_config_value(), "Best 1080p H.264 + AAC | bv*+ba/b | res:1080,vcodec:h264,acodec:aac");
+		assert_eq!(presets[2].to_config_value(), "Audio as MP3 192K | ba/b |  | mp3:192K");
+	}
+}
@@ -0,0 +1,166 @@
+const PROGRESS_PREFIX: &str = "[tubex-progress]";
+
+pub fn parse_destination(line: &str) -> Option<&str> {
+	if let Some(target) = line.strip_prefix("[Merger] Merging formats into ") {
+		return Some(target.trim().trim_matches('"'));
+	}
+	let (tag, path) = line.split_once("] Destination: ")?;
+	tag.starts_with('[').then_some(path.trim())
+}
+
+pub fn progress_template() -> String {
+	format!(
+		"download:{} %(progress.status)s|%(progress.downloaded_bytes)s|%(progress.total_bytes)s|%(progress.total_bytes_estimate)s|%(progress.speed)s|%(progress.eta)s|%(info.playlist_index)s|%(info.n_entries)s",
+		PROGRESS_PREFIX
+	)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgressInfo {
+	pub status: String,
+	pub percent: Option<f64>,
+	pub downloaded_bytes: Option<u64>,
+	pub total_bytes: Option<u64>,
+	pub speed: Option<f64>,
+	pub eta: Option<u64>,
+	pub playlist_index: Option<u32>,
+	pub playlist_count: Option<u32>,
+}
+
+impl ProgressInfo {
+	pub fn parse(line: &str) -> Option<Self> {
+		let fields: Vec<&str> = line.trim().strip_prefix(PROGRESS_PREFIX)?.trim().split('|').collect();
+		if fields.len() != 8 {
+			return None;
+		}
+		let num = |i: usize| fields[i].trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+		let downloaded_bytes = num(1).map(|v| v as u64);
+		let total_bytes = num(2).or_else(|| num(3)).map(|v| v as u64).filter(|t| *t > 0);
+		let percent = match (fields[0], downloaded_bytes, total_bytes) {
+			("finished", ..) => Some(100.0),
+			(_, Some(d), Some(t)) => Some((d as f64 / t as f64 * 100.0).min(100.0)),
+			_ => None,
+		};
+		Some(Self {
+			status: fields[0].trim().to_string(),
+			percent,
+			downloaded_bytes,
+			total_bytes,
+			speed: num(4),
+			eta: num(5).map(|v| v as u64),
+			playlist_index: num(6).map(|v| v as u32),
+			playlist_count: num(7).map(|v| v as u32),
+		})
+	}
+
+	pub fn summary(&self) -> String {
+		let mut parts = Vec::new();
+		if let (Some(i), Some(n)) = (self.playlist_index, self.playlist_count) {
+			parts.push(format!("Item {}/{}", i, n));
+		}
+		match self.status.as_str() {
+			"finished" => parts.push("Downloaded, processing...".into()),
+			"error" => parts.push("Download error".into()),
+			_ => {
+				parts.push(self.percent.map(|p| format!("{:.1}%", p)).unwrap_or_else(|| "?%".into()));
+				if let Some(t) = self.total_bytes {
+					parts.push(format!("of {}", format_bytes(t as f64)));
+				} else if let Some(d) = self.downloaded_bytes {
+					parts.push(format!("{} so far", format_bytes(d as f64)));
+				}
+				if let Some(s) = self.speed {
+					parts.push(format!("at {}/s", format_bytes(s)));
+				}
+				if let Some(e) = self.eta {
+					parts.push(format!("ETA {}:{:02}", e / 60, e % 60));
+				}
+			}
+		}
+		parts.join(" ")
+	}
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+	const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+	let mut value = bytes;
+	let mut unit = 0;
+	while value >= 1024.0 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	if unit == 0 { format!("{:.0}{}", value, UNITS[unit]) } else { format!("{:.1}{}", value, UNITS[unit]) }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_downloading_line() {
+		let p = ProgressInfo::parse("[tubex-progress] downloading|1048576|10485760|NA|524288.123456|18|NA|NA").unwrap();
+		assert_eq!(p.status, "downloading");
+		assert_eq!(p.downloaded_bytes, Some(1048576));
+		assert_eq!(p.total_bytes, Some(10485760));
+		assert_eq!(p.percent, Some(10.0));
+		assert_eq!(p.eta, Some(18));
+		assert_eq!(p.playlist_index, None);
+		assert_eq!(p.summary(), "10.0% of 10.0MiB at 512.0KiB/s ETA 0:18");
+	}
+
+	#[test]
+	fn falls_back_to_total_bytes_estimate() {
+		let p = ProgressInfo::parse("[tubex-progress] downloading|2621440|NA|5242880.0|1048576.0|2|NA|NA").unwrap();
+		assert_eq!(p.total_bytes, Some(5242880));
+		assert_eq!(p.percent, Some(50.0));
+	}
+
+	#[test]
+	fn all_fields_na() {
+		let p = ProgressInfo::parse("[tubex-progress] downloading|NA|NA|NA|NA|NA|NA|NA").unwrap();
+		assert_eq!(p, ProgressInfo { status: "downloading".into(), ..Default::default() });
+		assert_eq!(p.summary(), "?%");
+	}
+
+	#[test]
+	fn unknown_total_shows_bytes_so_far() {
+		let p = ProgressInfo::parse("[tubex-progress] downloading|2048|NA|NA|4096.0|NA|NA|NA").unwrap();
+		assert_eq!(p.percent, None);
+		assert_eq!(p.summary(), "?% 2.0KiB so far at 4.0KiB/s");
+	}
+
+	#[test]
+	fn finished_is_complete() {
+		let p = ProgressInfo::parse("[tubex-progress] finished|10485760|10485760|NA|NA|NA|NA|NA").unwrap();
+		assert_eq!(p.percent, Some(100.0));
+		assert_eq!(p.summary(), "Downloaded, processing...");
+	}
+
+	#[test]
+	fn playlist_position() {
+		let p = ProgressInfo::parse("[tubex-progress] downloading|524288|1048576|NA|262144.0|2|3|12").unwrap();
+		assert_eq!((p.playlist_index, p.playlist_count), (Some(3), Some(12)));
+		assert_eq!(p.summary(), "Item 3/12 50.0% of 1.0MiB at 256.0KiB/s ETA 0:02");
+	}
+
+	#[test]
+	fn ignores_other_lines() {
+		assert_eq!(ProgressInfo::parse("[download]  42.0% of   10.00MiB at  512.00KiB/s ETA 00:18"), None);
+		assert_eq!(ProgressInfo::parse("[youtube] dQw4w9WgXcQ: Downloading webpage"), None);
+		assert_eq!(ProgressInfo::parse("[tubex-progress] downloading|1|2"), None);
+		assert_eq!(ProgressInfo::parse(""), None);
+	}
+
+	#[test]
+	fn formats_bytes() {
+		assert_eq!(format_bytes(512.0), "512B");
+		assert_eq!(format_bytes(1536.0), "1.5KiB");
+		assert_eq!(format_bytes(3.0 * 1024.0 * 1024.0 * 1024.0), "3.0GiB");
+	}
+
+	#[test]
+	fn parses_destination_lines() {
+		assert_eq!(parse_destination("[download] Destination: Video [abc].f137.mp4"), Some("Video [abc].f137.mp4"));
+		assert_eq!(parse_destination("[Merger] Merging formats into \"Video [abc].mkv\""), Some("Video [abc].mkv"));
+		assert_eq!(parse_destination("[download] 100% of 10.00MiB"), None);
+	}
+}
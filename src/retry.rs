@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorClass {
+	RateLimited,
+	Network,
+	Fragment,
+	Server,
+}
+
+impl ErrorClass {
+	pub const ALL: [ErrorClass; 4] =
+		[ErrorClass::RateLimited, ErrorClass::Network, ErrorClass::Fragment, ErrorClass::Server];
+
+	pub fn key(self) -> &'static str {
+		match self {
+			ErrorClass::RateLimited => "rate_limit",
+			ErrorClass::Network => "network",
+			ErrorClass::Fragment => "fragment",
+			ErrorClass::Server => "server",
+		}
+	}
+
+	pub fn from_key(key: &str) -> Option<Self> { Self::ALL.into_iter().find(|c| c.key() == key.trim()) }
+
+	pub fn label(self) -> &'static str {
+		match self {
+			ErrorClass::RateLimited => "HTTP 429 / rate limit",
+			ErrorClass::Network => "Network timeout",
+			ErrorClass::Fragment => "Fragment failure",
+			ErrorClass::Server => "HTTP 5xx server error",
+		}
+	}
+
+	pub fn classify(line: &str) -> Option<Self> {
+		let l = line.to_ascii_lowercase();
+		let any = |needles: &[&str]| needles.iter().any(|n| l.contains(n));
+		if any(&["http error 429", "too many requests"]) {
+			Some(ErrorClass::RateLimited)
+		} else if l.contains("fragment") && any(&["not found", "giving up", "unable to continue"]) {
+			Some(ErrorClass::Fragment)
+		} else if any(&["http error 500", "http error 502", "http error 503", "http error 504"]) {
+			Some(ErrorClass::Server)
+		} else if any(&[
+			"timed out",
+			"connection reset",
+			"connection refused",
+			"connection aborted",
+			"remote end closed connection",
+			"incompleteread",
+			"temporary failure in name resolution",
+			"network is unreachable",
+		]) {
+			Some(ErrorClass::Network)
+		} else {
+			None
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+	pub max_attempts: u32,
+	pub base_delay_secs: u64,
+	pub retry_on: Vec<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self { Self { max_attempts: 3, base_delay_secs: 10, retry_on: ErrorClass::ALL.to_vec() } }
+}
+
+impl RetryPolicy {
+	pub fn should_retry(&self, attempt: u32, class: Option<ErrorClass>) -> bool {
+		attempt < self.max_attempts && class.is_some_and(|c| self.retry_on.contains(&c))
+	}
+
+	pub fn delay(&self, attempt: u32) -> Duration {
+		let factor = 1u64 << attempt.saturating_sub(1).min(16);
+		Duration::from_secs(self.base_delay_secs.saturating_mul(factor)).min(MAX_RETRY_DELAY)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn classifies_yt_dlp_errors() {
+		let cases = [
+			(
+				"ERROR: [youtube] abc: Unable to download webpage: HTTP Error 429: Too Many Requests",
+				ErrorClass::RateLimited,
+			),
+			("ERROR: unable to download video data: HTTP Error 503: Service Unavailable", ErrorClass::Server),
+			("ERROR: fragment 12 not found, unable to continue", ErrorClass::Fragment),
+			("[download] Got error: The read operation timed out. Retrying (1/10)...", ErrorClass::Network),
+			("ERROR: [Errno 104] Connection reset by peer", ErrorClass::Network),
+			("ERROR: <urlopen error [Errno -3] Temporary failure in name resolution>", ErrorClass::Network),
+		];
+		for (line, class) in cases {
+			assert_eq!(ErrorClass::classify(line), Some(class), "{}", line);
+		}
+		assert_eq!(ErrorClass::classify("ERROR: [youtube] abc: Video unavailable"), None);
+		assert_eq!(ErrorClass::classify("ERROR: unable to download video data: HTTP Error 403: Forbidden"), None);
+	}
+
+	#[test]
+	fn delay_doubles_up_to_the_cap() {
+		let policy = RetryPolicy::default();
+		let delays: Vec<u64> = (1..=9).map(|a| policy.delay(a).as_secs()).collect();
+		assert_eq!(delays, [10, 20, 40, 80, 160, 320, 640, 900, 900]);
+		assert_eq!(policy.delay(u32::MAX), MAX_RETRY_DELAY);
+	}
+
+	#[test]
+	fn stops_retrying_at_max_attempts() {
+		let policy = RetryPolicy { retry_on: vec![ErrorClass::Network], ..RetryPolicy::default() };
+		assert!(policy.should_retry(1, Some(ErrorClass::Network)));
+		assert!(policy.should_retry(2, Some(ErrorClass::Network)));
+		assert!(!policy.should_retry(3, Some(ErrorClass::Network)));
+		assert!(!policy.should_retry(1, Some(ErrorClass::Server)));
+		assert!(!policy.should_retry(1, None));
+	}
+}
@@ -1,7 +1,6 @@
 use std::{
 	io::{BufRead, BufReader},
-	os::windows::process::CommandExt,
-	process::{Command, Stdio},
+	process::Stdio,
 	sync::{
 		Arc, Mutex,
 		atomic::{AtomicBool, Ordering},
@@ -20,11 +19,10 @@ use crate::{
 	config::ConfigManager,
 	events::AppEvent,
 	options_dialog::{show_channel_action_dialog, show_selection_dialog},
+	platform,
 	video_info::VideoInfo,
 };
 
-const CREATE_NO_WINDOW: u32 = 0x08000000;
-
 enum SearchEvent {
 	Result(Vec<VideoInfo>, bool),
 	PlaylistsFetched(Vec<(String, String)>),
@@ -149,8 +147,7 @@ pub fn create_search_tab(
 		let tx = tx_search.clone();
 		let yt_path = yt_path_search.clone();
 		thread::spawn(move || {
-			let mut cmd = Command::new(&yt_path);
-			cmd.creation_flags(CREATE_NO_WINDOW);
+			let mut cmd = platform::command(&yt_path);
 			cmd.arg("--dump-json").arg("--flat-playlist").arg("--skip-download");
 			if start > 1 {
 				cmd.arg("--playlist-start").arg(start.to_string());
@@ -212,8 +209,7 @@ pub fn create_search_tab(
 		let tx = tx_list.clone();
 		let yt_path = yt_path_list.clone();
 		thread::spawn(move || {
-			let mut cmd = Command::new(&yt_path);
-			cmd.creation_flags(CREATE_NO_WINDOW);
+			let mut cmd = platform::command(&yt_path);
 			cmd.arg("--flat-playlist").arg("--print").arg("%(title)s:::%(url)s");
 			let mut full_url = url.clone();
 			if full_url.ends_with('/') {
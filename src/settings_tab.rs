@@ -1,10 +1,31 @@
-use std::sync::{Arc, Mutex};
+use std::{
+	sync::{Arc, Mutex, mpsc},
+	thread,
+};
 
-use wxdragon::prelude::*;
+use wxdragon::{TextCtrlStyle, prelude::*};
 
-use crate::config::ConfigManager;
+use crate::{
+	config::ConfigManager,
+	config_dialog::{show_output_templates_dialog, show_presets_dialog},
+	download_manager::DownloadManager,
+	platform,
+	retry::{ErrorClass, RetryPolicy},
+	version_manager::VersionManager,
+};
 
-pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigManager>>) -> Panel {
+enum VersionEvent {
+	Checked(Option<String>, String, Result<String, String>),
+	Updated(Result<String, String>),
+	RolledBack(Result<String, String>),
+}
+
+pub fn create_settings_tab(
+	parent: &Notebook,
+	config_manager: Arc<Mutex<ConfigManager>>,
+	version_manager: VersionManager,
+	download_manager: Arc<DownloadManager>,
+) -> Panel {
 	let panel = Panel::builder(parent).with_style(wxdragon::PanelStyle::TabTraversal).build();
 	let sizer = BoxSizer::builder(Orientation::Vertical).build();
 
@@ -57,8 +78,37 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 		},
 	);
 	channel_sizer.add(&channel_choice, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	channel_sizer.add(
+		&StaticText::builder(&panel).with_label("Pin Version:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let pin_text = TextCtrl::builder(&panel).build();
+	pin_text.set_value(
+		&config_manager.lock().expect("Config manager lock failed").get_pinned_version().unwrap_or_default(),
+	);
+	channel_sizer.add(&pin_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
 	sizer.add_sizer(&channel_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
 
+	let version_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	let version_label = StaticText::builder(&panel).with_label("yt-dlp version: not checked").build();
+	version_sizer.add(&version_label, 1, SizerFlag::AlignCenterVertical | SizerFlag::All, 5);
+	let update_check_btn = Button::builder(&panel).with_label("Check for Updates").build();
+	let update_btn = Button::builder(&panel).with_label("Update Now").build();
+	let rollback_btn = Button::builder(&panel).with_label("Roll Back").build();
+	rollback_btn.enable(VersionManager::has_rollback(
+		&config_manager.lock().expect("Config manager lock failed").get_yt_dlp_path(),
+	));
+	version_sizer.add(&update_check_btn, 0, SizerFlag::All, 5);
+	version_sizer.add(&update_btn, 0, SizerFlag::All, 5);
+	version_sizer.add(&rollback_btn, 0, SizerFlag::All, 5);
+	sizer.add_sizer(&version_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let auto_update_chk = CheckBox::builder(&panel).with_label("Update yt-dlp automatically at startup").build();
+	auto_update_chk.set_value(config_manager.lock().expect("Config manager lock failed").get_auto_update());
+	sizer.add(&auto_update_chk, 0, SizerFlag::All, 5);
+
 	let ffmpeg_sizer = BoxSizer::builder(Orientation::Horizontal).build();
 	ffmpeg_sizer.add(
 		&StaticText::builder(&panel).with_label("FFmpeg Path:").build(),
@@ -85,10 +135,245 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 	flags_sizer.add(&flags_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
 	sizer.add_sizer(&flags_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
 
+	let mirrors_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	mirrors_sizer.add(
+		&StaticText::builder(&panel).with_label("Tool Mirrors:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let mirrors_text = TextCtrl::builder(&panel).build();
+	mirrors_text.set_value(&config_manager.lock().expect("Config manager lock failed").get_tool_mirrors().join(", "));
+	mirrors_sizer.add(&mirrors_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	sizer.add_sizer(&mirrors_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let sponsorblock_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	sponsorblock_sizer.add(
+		&StaticText::builder(&panel).with_label("SponsorBlock API (empty for default):").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let sponsorblock_text = TextCtrl::builder(&panel).build();
+	sponsorblock_text.set_value(&config_manager.lock().expect("Config manager lock failed").get_sponsorblock_api());
+	sponsorblock_sizer.add(&sponsorblock_text, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	sizer.add_sizer(&sponsorblock_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let concurrency_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	concurrency_sizer.add(
+		&StaticText::builder(&panel).with_label("Max Concurrent Downloads:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let concurrency_spin = SpinCtrl::builder(&panel).with_range(1, 16).build();
+	concurrency_spin
+		.set_value(config_manager.lock().expect("Config manager lock failed").get_max_concurrent_downloads() as i32);
+	concurrency_sizer.add(&concurrency_spin, 0, SizerFlag::All, 5);
+	sizer.add_sizer(&concurrency_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let retry_policy = config_manager.lock().expect("Config manager lock failed").get_retry_policy();
+	let retry_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	retry_sizer.add(
+		&StaticText::builder(&panel).with_label("Retry Attempts:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let attempts_spin = SpinCtrl::builder(&panel).with_range(1, 10).build();
+	attempts_spin.set_value(retry_policy.max_attempts as i32);
+	retry_sizer.add(&attempts_spin, 0, SizerFlag::All, 5);
+	retry_sizer.add(
+		&StaticText::builder(&panel).with_label("Base Delay (s):").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let delay_spin = SpinCtrl::builder(&panel).with_range(1, 600).build();
+	delay_spin.set_value(retry_policy.base_delay_secs as i32);
+	retry_sizer.add(&delay_spin, 0, SizerFlag::All, 5);
+	let retry_checks: Vec<(ErrorClass, CheckBox)> = ErrorClass::ALL
+		.into_iter()
+		.map(|class| {
+			let chk = CheckBox::builder(&panel).with_label(class.label()).build();
+			chk.set_value(retry_policy.retry_on.contains(&class));
+			retry_sizer.add(&chk, 0, SizerFlag::AlignCenterVertical | SizerFlag::All, 5);
+			(class, chk)
+		})
+		.collect();
+	sizer.add_sizer(&retry_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let archive_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	archive_sizer.add(
+		&StaticText::builder(&panel).with_label("Download Archive:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let archive_choice = Choice::builder(&panel)
+		.with_choices(vec!["Off".into(), "One per download folder".into(), "Global".into()])
+		.build();
+	archive_choice.set_selection(
+		match config_manager.lock().expect("Config manager lock failed").get_archive_mode().as_str() {
+			"per_folder" => 1,
+			"global" => 2,
+			_ => 0,
+		},
+	);
+	archive_sizer.add(&archive_choice, 0, SizerFlag::All, 5);
+	sizer.add_sizer(&archive_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let preset_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	preset_sizer.add(
+		&StaticText::builder(&panel).with_label("Default Preset:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let preset_choice = Choice::builder(&panel).build();
+	preset_sizer.add(&preset_choice, 1, SizerFlag::Expand | SizerFlag::All, 5);
+	let presets_btn = Button::builder(&panel).with_label("Manage Presets...").build();
+	preset_sizer.add(&presets_btn, 0, SizerFlag::All, 5);
+	sizer.add_sizer(&preset_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+	let fill_presets = move |cfg: &ConfigManager, selected: Option<String>| {
+		preset_choice.clear();
+		preset_choice.append("None (show the options dialog)");
+		preset_choice.set_selection(0);
+		for (i, preset) in cfg.get_presets().iter().enumerate() {
+			preset_choice.append(&preset.name);
+			if selected.as_deref() == Some(preset.name.as_str()) {
+				preset_choice.set_selection(i as u32 + 1);
+			}
+		}
+	};
+	{
+		let cfg = config_manager.lock().expect("Config manager lock failed");
+		fill_presets(&cfg, cfg.get_default_preset().map(|p| p.name));
+	}
+
+	let templates_sizer = BoxSizer::builder(Orientation::Horizontal).build();
+	templates_sizer.add(
+		&StaticText::builder(&panel).with_label("Output Filenames:").build(),
+		0,
+		SizerFlag::AlignCenterVertical | SizerFlag::All,
+		5,
+	);
+	let templates_btn = Button::builder(&panel).with_label("Edit Templates...").build();
+	templates_sizer.add(&templates_btn, 0, SizerFlag::All, 5);
+	sizer.add_sizer(&templates_sizer, 0, SizerFlag::Expand | SizerFlag::All, 5);
+
+	let keep_partial_chk =
+		CheckBox::builder(&panel).with_label("Keep partial files when a download is cancelled").build();
+	keep_partial_chk.set_value(config_manager.lock().expect("Config manager lock failed").get_keep_partial_files());
+	sizer.add(&keep_partial_chk, 0, SizerFlag::All, 5);
+
+	let check_format_chk = CheckBox::builder(&panel)
+		.with_label("Check the format selector with yt-dlp --simulate before downloading")
+		.build();
+	check_format_chk.set_value(config_manager.lock().expect("Config manager lock failed").get_check_format_selector());
+	sizer.add(&check_format_chk, 0, SizerFlag::All, 5);
+
+	let playlist_subfolder_chk =
+		CheckBox::builder(&panel).with_label("Save playlists into a subfolder named after the playlist").build();
+	playlist_subfolder_chk
+		.set_value(config_manager.lock().expect("Config manager lock failed").get_playlist_subfolder());
+	sizer.add(&playlist_subfolder_chk, 0, SizerFlag::All, 5);
+
+	sizer.add(&StaticText::builder(&panel).with_label("yt-dlp Update Log:").build(), 0, SizerFlag::All, 5);
+	let update_log = TextCtrl::builder(&panel).with_style(TextCtrlStyle::MultiLine | TextCtrlStyle::ReadOnly).build();
+	sizer.add(&update_log, 1, SizerFlag::Expand | SizerFlag::All, 5);
+
 	let save_btn = Button::builder(&panel).with_label("Save Settings").build();
 	sizer.add(&save_btn, 0, SizerFlag::All | SizerFlag::AlignRight, 10);
 	panel.set_sizer(sizer, true);
 
+	let (tx, rx) = mpsc::channel::<VersionEvent>();
+	let timer = Box::leak(Box::new(Timer::new(&panel)));
+	let vm_timer = version_manager.clone();
+	let cfg_timer = config_manager.clone();
+	let log_timer = update_log;
+	let label_timer = version_label;
+	let rollback_timer = rollback_btn;
+	let pin_timer = pin_text;
+	let update_timer = update_btn;
+	let panel_timer = panel;
+	let mut shown_log_len = 0;
+	timer.start(500, false);
+	timer.on_tick(move |_| {
+		let log = vm_timer.get_log();
+		if log.len() != shown_log_len {
+			shown_log_len = log.len();
+			log_timer.set_value(&log);
+			log_timer.set_insertion_point_end();
+		}
+		while let Ok(event) = rx.try_recv() {
+			let (msg, caption) = match event {
+				VersionEvent::Checked(installed, channel, latest) => {
+					let installed = installed.unwrap_or_else(|| "not found".into());
+					let status = match &latest {
+						Ok(l) if *l == installed => format!("yt-dlp {} is up to date on {}", installed, channel),
+						Ok(l) => format!("yt-dlp {} installed, {} available on {}", installed, l, channel),
+						Err(e) => format!("yt-dlp {} installed ({})", installed, e),
+					};
+					label_timer.set_label(&status);
+					continue;
+				}
+				VersionEvent::Updated(Ok(v)) => (format!("yt-dlp is at version {}.", v), "yt-dlp Update"),
+				VersionEvent::Updated(Err(e)) => (format!("Update failed: {}", e), "Error"),
+				VersionEvent::RolledBack(Ok(v)) => {
+					pin_timer.set_value(
+						&cfg_timer.lock().expect("Config manager lock failed").get_pinned_version().unwrap_or_default(),
+					);
+					(format!("Rolled back to yt-dlp {}.", v), "yt-dlp Rollback")
+				}
+				VersionEvent::RolledBack(Err(e)) => (format!("Rollback failed: {}", e), "Error"),
+			};
+			update_timer.enable(true);
+			rollback_timer.enable(VersionManager::has_rollback(
+				&cfg_timer.lock().expect("Config manager lock failed").get_yt_dlp_path(),
+			));
+			label_timer.set_label(&msg);
+			let _ = MessageDialog::builder(&panel_timer, &msg, caption).build().show_modal();
+		}
+	});
+
+	let cfg_version = config_manager.clone();
+	let tx_version = tx.clone();
+	let label_version = version_label;
+	update_check_btn.on_click(move |_| {
+		let (path, channel) = {
+			let c = cfg_version.lock().expect("Config manager lock failed");
+			(c.get_yt_dlp_path(), c.get_update_channel())
+		};
+		label_version.set_label("Checking for updates...");
+		let tx = tx_version.clone();
+		thread::spawn(move || {
+			let latest = VersionManager::latest_version(&channel);
+			let _ = tx.send(VersionEvent::Checked(VersionManager::installed_version(&path), channel, latest));
+		});
+	});
+
+	let cfg_update = config_manager.clone();
+	let vm_update = version_manager.clone();
+	let tx_update = tx.clone();
+	let update_btn_click = update_btn;
+	update_btn.on_click(move |_| {
+		update_btn_click.enable(false);
+		let (cfg, vm, tx) = (cfg_update.clone(), vm_update.clone(), tx_update.clone());
+		thread::spawn(move || {
+			let _ = tx.send(VersionEvent::Updated(vm.update(&cfg)));
+		});
+	});
+
+	let cfg_rollback = config_manager.clone();
+	let vm_rollback = version_manager.clone();
+	rollback_btn.on_click(move |_| {
+		let (cfg, vm, tx) = (cfg_rollback.clone(), vm_rollback.clone(), tx.clone());
+		thread::spawn(move || {
+			let _ = tx.send(VersionEvent::RolledBack(vm.rollback(&cfg)));
+		});
+	});
+
 	let panel_clone = panel;
 	let path_text_clone = path_text;
 	browse_btn.on_click(move |_| {
@@ -106,7 +391,7 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 	ytdlp_browse.on_click(move |_| {
 		let dialog = FileDialog::builder(&panel_ytdlp)
 			.with_message("Select yt-dlp Executable")
-			.with_wildcard("Executables (*.exe)|*.exe|All Files (*.*)|*.*")
+			.with_wildcard(platform::EXECUTABLE_WILDCARD)
 			.build();
 		if dialog.show_modal() == wxdragon::id::ID_OK
 			&& let Some(p) = dialog.get_path()
@@ -120,7 +405,7 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 	ffmpeg_browse.on_click(move |_| {
 		let dialog = FileDialog::builder(&panel_ffmpeg)
 			.with_message("Select FFmpeg Executable")
-			.with_wildcard("Executables (*.exe)|*.exe|All Files (*.*)|*.*")
+			.with_wildcard(platform::EXECUTABLE_WILDCARD)
 			.build();
 		if dialog.show_modal() == wxdragon::id::ID_OK
 			&& let Some(p) = dialog.get_path()
@@ -132,7 +417,8 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 	let ytdlp_text_check = ytdlp_text;
 	let panel_check = panel;
 	check_btn.on_click(move |_| {
-		match std::process::Command::new(ytdlp_text_check.get_value()).arg("--version").output() {
+		let output = platform::command(ytdlp_text_check.get_value()).arg("--version").output();
+		match output {
 			Ok(out) => {
 				let msg = if out.status.success() {
 					format!("Success! Version: {}", String::from_utf8_lossy(&out.stdout).trim())
@@ -149,12 +435,36 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 		}
 	});
 
+	let cfg_presets = config_manager.clone();
+	let panel_presets = panel;
+	presets_btn.on_click(move |_| {
+		let selected = preset_choice.get_selection().filter(|s| *s > 0).and_then(|s| preset_choice.get_string(s));
+		show_presets_dialog(&panel_presets, cfg_presets.clone());
+		fill_presets(&cfg_presets.lock().expect("Config manager lock failed"), selected);
+	});
+
+	let panel_templates = panel;
+	let cfg_templates = config_manager.clone();
+	templates_btn.on_click(move |_| show_output_templates_dialog(&panel_templates, cfg_templates.clone()));
+
 	let cfg_save = config_manager.clone();
 	let path_save = path_text;
 	let ytdlp_save = ytdlp_text;
 	let channel_save = channel_choice;
 	let ffmpeg_save = ffmpeg_text;
 	let flags_save = flags_text;
+	let mirrors_save = mirrors_text;
+	let sponsorblock_save = sponsorblock_text;
+	let pin_save = pin_text;
+	let auto_update_save = auto_update_chk;
+	let concurrency_save = concurrency_spin;
+	let attempts_save = attempts_spin;
+	let delay_save = delay_spin;
+	let keep_partial_save = keep_partial_chk;
+	let archive_save = archive_choice;
+	let check_format_save = check_format_chk;
+	let playlist_subfolder_save = playlist_subfolder_chk;
+	let preset_save = preset_choice;
 	let panel_save = panel;
 
 	save_btn.on_click(move |_| {
@@ -168,6 +478,28 @@ pub fn create_settings_tab(parent: &Notebook, config_manager: Arc<Mutex<ConfigMa
 		});
 		cfg.set_ffmpeg_path(&ffmpeg_save.get_value());
 		cfg.set_global_flags(&flags_save.get_value());
+		cfg.set_tool_mirrors(&mirrors_save.get_value());
+		cfg.set_sponsorblock_api(&sponsorblock_save.get_value());
+		cfg.set_pinned_version(&pin_save.get_value());
+		cfg.set_auto_update(auto_update_save.get_value());
+		cfg.set_max_concurrent_downloads(concurrency_save.value() as usize);
+		download_manager.set_max_concurrent(concurrency_save.value() as usize);
+		cfg.set_keep_partial_files(keep_partial_save.get_value());
+		cfg.set_check_format_selector(check_format_save.get_value());
+		cfg.set_playlist_subfolder(playlist_subfolder_save.get_value());
+		cfg.set_default_preset(
+			preset_save.get_selection().filter(|s| *s > 0).and_then(|s| preset_save.get_string(s)).as_deref(),
+		);
+		cfg.set_archive_mode(match archive_save.get_selection() {
+			Some(1) => "per_folder",
+			Some(2) => "global",
+			_ => "off",
+		});
+		cfg.set_retry_policy(&RetryPolicy {
+			max_attempts: attempts_save.value() as u32,
+			base_delay_secs: delay_save.value() as u64,
+			retry_on: retry_checks.iter().filter(|(_, chk)| chk.get_value()).map(|(class, _)| *class).collect(),
+		});
 		cfg.flush();
 		let _ = MessageDialog::builder(&panel_save, "Settings saved successfully.", "Info").build().show_modal();
 	});
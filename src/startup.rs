@@ -1,13 +1,13 @@
 use std::{
-	fs::{self, File},
+	fs::{self, File, OpenOptions},
 	io::{self, BufReader, Read, Write},
 	path::{Path, PathBuf},
-	process::Command,
 	sync::{Arc, Mutex, mpsc::Sender},
-	thread,
 };
 
-use crate::{config::ConfigManager, events::AppEvent};
+use sha2::{Digest, Sha256};
+
+use crate::{config::ConfigManager, events::AppEvent, platform};
 
 pub fn check_dependencies(cfg: &Arc<Mutex<ConfigManager>>) -> (bool, bool) {
 	let (yt, ff) = {
@@ -15,7 +15,7 @@ pub fn check_dependencies(cfg: &Arc<Mutex<ConfigManager>>) -> (bool, bool) {
 		(c.get_yt_dlp_path(), c.get_ffmpeg_path())
 	};
 	let check_tool = |path: &str, default: &str, arg: &str| -> bool {
-		let try_run = |p: &str| Command::new(p).arg(arg).output().is_ok_and(|o| o.status.success());
+		let try_run = |p: &str| platform::command(p).arg(arg).output().is_ok_and(|o| o.status.success());
 		let p = if path.trim().is_empty() { default } else { path };
 		try_run(p)
 			|| (cfg!(windows) && !p.to_lowercase().ends_with(".exe") && try_run(&format!("{}.exe", p)))
@@ -24,101 +24,351 @@ pub fn check_dependencies(cfg: &Arc<Mutex<ConfigManager>>) -> (bool, bool) {
 	(check_tool(&yt, "yt-dlp", "--version"), check_tool(&ff, "ffmpeg", "-version"))
 }
 
-pub fn update_ytdlp(cfg: &Arc<Mutex<ConfigManager>>) {
-	let (path, channel) = {
-		let c = cfg.lock().expect("Config manager lock failed");
-		(c.get_yt_dlp_path(), c.get_update_channel())
-	};
-	if Path::new(&path)
-		.parent()
-		.is_some_and(|p| p.as_os_str().is_empty() || p == std::env::current_dir().unwrap_or_default())
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+pub struct ToolSource {
+	pub url: String,
+	pub checksums_url: String,
+}
+
+pub struct ToolSources {
+	pub yt_dlp: Vec<ToolSource>,
+	pub ffmpeg: Vec<ToolSource>,
+}
+
+impl ToolSources {
+	pub fn for_current_platform(mirrors: &[String]) -> Self {
+		let yt_dlp_asset = if cfg!(windows) {
+			"yt-dlp.exe"
+		} else if cfg!(target_os = "macos") {
+			"yt-dlp_macos"
+		} else if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+			"yt-dlp_linux"
+		} else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+			"yt-dlp_linux_aarch64"
+		} else {
+			"yt-dlp"
+		};
+		let (ffmpeg_url, ffmpeg_checksums_url) = if cfg!(windows) {
+			(
+				"https://www.gyan.dev/ffmpeg/builds/ffmpeg-release-essentials.zip",
+				"https://www.gyan.dev/ffmpeg/builds/ffmpeg-release-essentials.zip.sha256",
+			)
+		} else if cfg!(target_arch = "aarch64") {
+			(
+				"https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/ffmpeg-master-latest-linuxarm64-gpl.tar.xz",
+				"https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/checksums.sha256",
+			)
+		} else {
+			(
+				"https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/ffmpeg-master-latest-linux64-gpl.tar.xz",
+				"https://github.com/BtbN/FFmpeg-Builds/releases/download/latest/checksums.sha256",
+			)
+		};
+		let with_mirrors = |url: String, checksums_url: String| -> Vec<ToolSource> {
+			let asset = url.rsplit('/').next().unwrap_or_default().to_string();
+			let mirrored = mirrors.iter().map(|m| ToolSource {
+				url: format!("{}/{}", m.trim_end_matches('/'), asset),
+				checksums_url: checksums_url.clone(),
+			});
+			std::iter::once(ToolSource { url, checksums_url: checksums_url.clone() }).chain(mirrored).collect()
+		};
+		Self {
+			yt_dlp: with_mirrors(
+				format!("https://github.com/yt-dlp/yt-dlp/releases/latest/download/{}", yt_dlp_asset),
+				"https://github.com/yt-dlp/yt-dlp/releases/latest/download/SHA2-256SUMS".into(),
+			),
+			ffmpeg: with_mirrors(ffmpeg_url.to_string(), ffmpeg_checksums_url.to_string()),
+		}
+	}
+}
+
+pub fn tools_dir() -> PathBuf {
+	let exe_path = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
+	exe_path.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn expected_checksum(sums: &str, asset: &str) -> Option<String> {
+	sums.lines().find_map(|line| {
+		let mut parts = line.split_whitespace();
+		let hash = parts.next()?;
+		let matches = match parts.next() {
+			Some(name) => name.trim_start_matches('*') == asset,
+			None => true,
+		};
+		(matches && hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_lowercase())
+	})
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+	let mut file = File::open(path)?;
+	let mut hasher = Sha256::new();
+	io::copy(&mut file, &mut hasher)?;
+	Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn partial_path(target_dir: &Path, filename: &str, source: &ToolSource) -> PathBuf {
+	let key = format!("{:x}", Sha256::digest(source.url.as_bytes()));
+	target_dir.join(format!("{}.{}.part", filename, &key[..12]))
+}
+
+fn fetch_partial(url: &str, temp_path: &Path, mut on_progress: impl FnMut(u64, u64)) -> Result<(), String> {
+	let offset = fs::metadata(temp_path).map(|m| m.len()).unwrap_or(0);
+	let mut request = ureq::get(url).config().http_status_as_error(false).build();
+	if offset > 0 {
+		request = request.header("Range", format!("bytes={}-", offset));
+	}
+	let resp = request.call().map_err(|e| format!("Request failed: {}", e))?;
+	if resp.status().as_u16() == 416 && offset > 0 {
+		let total = resp
+			.headers()
+			.get("content-range")
+			.and_then(|h| h.to_str().ok())
+			.and_then(|r| r.strip_prefix("bytes */"))
+			.and_then(|t| t.trim().parse::<u64>().ok());
+		if total == Some(offset) {
+			return Ok(());
+		}
+		fs::remove_file(temp_path).map_err(|e| format!("Failed to discard partial download: {}", e))?;
+		return fetch_partial(url, temp_path, on_progress);
+	}
+	if !resp.status().is_success() {
+		return Err(format!("Request failed: HTTP {}", resp.status().as_u16()));
+	}
+	let resumed = offset > 0 && resp.status().as_u16() == 206;
+	let remaining = resp
+		.headers()
+		.get("content-length")
+		.and_then(|h| h.to_str().ok())
+		.and_then(|s| s.parse::<u64>().ok())
+		.unwrap_or(0);
+	let mut downloaded = if resumed { offset } else { 0 };
+	let total_size = downloaded + remaining;
+	let mut file = if resumed { OpenOptions::new().append(true).open(temp_path) } else { File::create(temp_path) }
+		.map_err(|e| format!("Failed to create file: {}", e))?;
+	let mut reader = resp.into_body().into_reader();
+	let mut buffer = [0; 8192];
+	loop {
+		let n = reader.read(&mut buffer).map_err(|e| format!("Read error: {}", e))?;
+		if n == 0 {
+			break;
+		}
+		file.write_all(&buffer[..n]).map_err(|e| format!("Write error: {}", e))?;
+		downloaded += n as u64;
+		if remaining > 0 {
+			on_progress(downloaded, total_size);
+		}
+	}
+	if remaining > 0 && downloaded < total_size {
+		return Err(format!("Transfer ended early ({} of {} bytes)", downloaded, total_size));
+	}
+	Ok(())
+}
+
+enum ArchiveKind {
+	Zip,
+	Tar,
+	TarGz,
+	TarXz,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+	let name = path.file_name()?.to_string_lossy().to_lowercase();
+	if name.ends_with(".zip") {
+		Some(ArchiveKind::Zip)
+	} else if name.ends_with(".tar.xz") || name.ends_with(".txz") {
+		Some(ArchiveKind::TarXz)
+	} else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+		Some(ArchiveKind::TarGz)
+	} else if name.ends_with(".tar") {
+		Some(ArchiveKind::Tar)
+	} else {
+		None
+	}
+}
+
+fn bundled_tool_name(name: &str) -> Option<String> {
+	let name = name.to_lowercase();
+	if ["ffmpeg", "ffprobe", "ffplay"].iter().any(|tool| name == platform::exe_name(tool)) {
+		Some(name)
+	} else if ["yt-dlp", "yt-dlp_x86", "yt-dlp_linux", "yt-dlp_linux_aarch64", "yt-dlp_macos"]
+		.iter()
+		.any(|tool| name == platform::exe_name(tool))
 	{
-		thread::spawn(move || {
-			let mut cmd = Command::new(&path);
-			cmd.arg("-U");
-			if !channel.is_empty() && channel != "stable" {
-				cmd.arg("--update-to").arg(&channel);
+		Some(platform::exe_name("yt-dlp"))
+	} else {
+		None
+	}
+}
+
+fn extract_tools(archive_path: &Path, target_dir: &Path) -> Result<Vec<String>, String> {
+	let kind = archive_kind(archive_path)
+		.ok_or_else(|| "Unsupported archive type (expected .zip, .tar, .tar.gz or .tar.xz)".to_string())?;
+	let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+	let mut installed = Vec::new();
+	let mut install = |entry_name: &str, reader: &mut dyn Read| -> Result<(), String> {
+		let Some(name) = bundled_tool_name(entry_name) else {
+			return Ok(());
+		};
+		let dest_path = target_dir.join(&name);
+		let temp_path = target_dir.join(format!("{}.part", name));
+		let mut outfile = File::create(&temp_path).map_err(|e| format!("Failed to create {}: {}", name, e))?;
+		if let Err(e) = io::copy(reader, &mut outfile) {
+			let _ = fs::remove_file(&temp_path);
+			return Err(format!("Extraction failed for {}: {}", name, e));
+		}
+		drop(outfile);
+		platform::set_executable(&temp_path).map_err(|e| format!("Failed to set permissions on {}: {}", name, e))?;
+		fs::rename(&temp_path, &dest_path).map_err(|e| format!("Failed to move {} into place: {}", name, e))?;
+		if !installed.contains(&name) {
+			installed.push(name);
+		}
+		Ok(())
+	};
+
+	if let ArchiveKind::Zip = kind {
+		let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Zip error: {}", e))?;
+		for i in 0..archive.len() {
+			let mut file = archive.by_index(i).map_err(|e| format!("Zip file error: {}", e))?;
+			if file.is_file()
+				&& let Some(path) = file.enclosed_name()
+				&& let Some(name) = path.file_name().and_then(|n| n.to_str())
+			{
+				install(name, &mut file)?;
 			}
-			let _ = cmd.output();
-		});
+		}
+	} else {
+		let reader: Box<dyn Read> = match kind {
+			ArchiveKind::TarXz => Box::new(xz2::read::XzDecoder::new(BufReader::new(file))),
+			ArchiveKind::TarGz => Box::new(flate2::read::GzDecoder::new(BufReader::new(file))),
+			_ => Box::new(BufReader::new(file)),
+		};
+		let mut archive = tar::Archive::new(reader);
+		for entry in archive.entries().map_err(|e| format!("Tar error: {}", e))? {
+			let mut entry = entry.map_err(|e| format!("Tar entry error: {}", e))?;
+			if !entry.header().entry_type().is_file() {
+				continue;
+			}
+			let path = entry.path().map_err(|e| format!("Tar entry error: {}", e))?.into_owned();
+			if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+				install(name, &mut entry)?;
+			}
+		}
 	}
+	Ok(installed)
+}
+
+pub fn install_from_archive(
+	archive_path: &Path,
+	target_dir: &Path,
+	tx: Sender<AppEvent>,
+) -> Result<Vec<String>, String> {
+	fs::create_dir_all(target_dir).map_err(|e| e.to_string())?;
+	let _ = tx.send(AppEvent::DownloadProgress("Extracting archive...".into(), 10));
+	let installed = extract_tools(archive_path, target_dir)?;
+	if installed.is_empty() {
+		return Err(format!("No yt-dlp or ffmpeg executables found in {}", archive_path.display()));
+	}
+	let _ = tx.send(AppEvent::DownloadProgress("Done.".into(), 100));
+	Ok(installed)
 }
 
 pub fn download_tools_script(
 	target_dir: &Path,
+	sources: &ToolSources,
 	tx: Sender<AppEvent>,
 	missing_yt: bool,
 	missing_ff: bool,
-) -> Result<(), String> {
-	let result: Result<(), String> = try {
+) -> Result<Vec<String>, String> {
+	let result: Result<Vec<String>, String> = try {
+		let mut installed = Vec::new();
 		if !target_dir.exists() {
 			fs::create_dir_all(target_dir).map_err(|e| e.to_string())?;
 		}
 
-		let download_file = |url: &str, filename: &str, start_pct: i32, end_pct: i32| -> Result<PathBuf, String> {
+		let download_file = |sources: &[ToolSource],
+		                     filename: &str,
+		                     start_pct: i32,
+		                     end_pct: i32|
+		 -> Result<PathBuf, String> {
 			let dest_path = target_dir.join(filename);
-			let _ = tx.send(AppEvent::DownloadProgress(format!("Downloading {}...", filename), start_pct));
-			let resp = ureq::get(url).call().map_err(|e| format!("Request failed: {}", e))?;
-			let total_size = resp
-				.headers()
-				.get("content-length")
-				.and_then(|h| h.to_str().ok())
-				.and_then(|s| s.parse::<u64>().ok())
-				.unwrap_or(0);
-			let mut reader = resp.into_body().into_reader();
-			let mut file = File::create(&dest_path).map_err(|e| format!("Failed to create file: {}", e))?;
-			let mut buffer = [0; 8192];
-			let mut downloaded: u64 = 0;
-			loop {
-				let n = reader.read(&mut buffer).map_err(|e| format!("Read error: {}", e))?;
-				if n == 0 {
-					break;
+			let mut last_error = format!("No download source configured for {}", filename);
+			for source in sources {
+				let temp_path = partial_path(target_dir, filename, source);
+				let asset = source.url.rsplit('/').next().unwrap_or(filename);
+				let _ =
+					tx.send(AppEvent::DownloadProgress(format!("Fetching checksums for {}...", filename), start_pct));
+				let expected = match ureq::get(&source.checksums_url)
+					.call()
+					.and_then(|mut r| r.body_mut().read_to_string())
+					.map_err(|e| format!("Failed to fetch checksums for {}: {}", asset, e))
+					.and_then(|sums| {
+						expected_checksum(&sums, asset).ok_or_else(|| format!("No published checksum for {}", asset))
+					}) {
+					Ok(hash) => hash,
+					Err(e) => {
+						last_error = e;
+						continue;
+					}
+				};
+
+				let mut fetched = Err(String::new());
+				for _ in 0..DOWNLOAD_ATTEMPTS {
+					fetched = fetch_partial(&source.url, &temp_path, |downloaded, total| {
+						let pct =
+							start_pct + ((downloaded as f64 / total as f64) * (end_pct - start_pct) as f64) as i32;
+						let _ =
+							tx.send(AppEvent::DownloadProgress(format!("Downloading {}... ({}%)", filename, pct), pct));
+					});
+					if fetched.is_ok() {
+						break;
+					}
+				}
+				if let Err(e) = fetched {
+					last_error = format!("Download of {} from {} failed: {}", filename, source.url, e);
+					continue;
+				}
+
+				let _ = tx.send(AppEvent::DownloadProgress(format!("Verifying {}...", filename), end_pct));
+				let actual = sha256_file(&temp_path).map_err(|e| format!("Failed to hash {}: {}", filename, e))?;
+				if actual != expected {
+					let _ = fs::remove_file(&temp_path);
+					last_error = format!("Checksum mismatch for {} (expected {}, got {})", asset, expected, actual);
+					continue;
 				}
-				file.write_all(&buffer[..n]).map_err(|e| format!("Write error: {}", e))?;
-				downloaded += n as u64;
-				if total_size > 0 {
-					let pct =
-						start_pct + ((downloaded as f64 / total_size as f64) * (end_pct - start_pct) as f64) as i32;
-					let _ = tx.send(AppEvent::DownloadProgress(format!("Downloading {}... ({}%)", filename, pct), pct));
+				fs::rename(&temp_path, &dest_path)
+					.map_err(|e| format!("Failed to move {} into place: {}", filename, e))?;
+				for other in sources {
+					let _ = fs::remove_file(partial_path(target_dir, filename, other));
 				}
+				return Ok(dest_path);
 			}
-			Ok(dest_path)
+			Err(last_error)
 		};
 
 		if missing_yt {
-			download_file("https://github.com/yt-dlp/yt-dlp/releases/latest/download/yt-dlp.exe", "yt-dlp.exe", 0, 30)?;
+			let yt_path = download_file(&sources.yt_dlp, &platform::exe_name("yt-dlp"), 0, 30)?;
+			platform::set_executable(&yt_path).map_err(|e| format!("Failed to set permissions on yt-dlp: {}", e))?;
+			installed.push(platform::exe_name("yt-dlp"));
 		}
 
 		if missing_ff {
-			let zip_path = download_file(
-				"https://www.gyan.dev/ffmpeg/builds/ffmpeg-release-essentials.zip",
-				"ffmpeg.zip",
-				30,
-				90,
-			)?;
+			let archive_name = if sources.ffmpeg.first().is_some_and(|s| s.url.ends_with(".tar.xz")) {
+				"ffmpeg.tar.xz"
+			} else {
+				"ffmpeg.zip"
+			};
+			let archive_path = download_file(&sources.ffmpeg, archive_name, 30, 90)?;
 			let _ = tx.send(AppEvent::DownloadProgress("Extracting ffmpeg...".into(), 90));
-			let file = File::open(&zip_path).map_err(|e| format!("Failed to open zip: {}", e))?;
-			let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Zip error: {}", e))?;
-			let mut found_any = false;
-			for i in 0..archive.len() {
-				let mut file = archive.by_index(i).map_err(|e| format!("Zip file error: {}", e))?;
-				if let Some(path) = file.enclosed_name().map(|p| p.to_path_buf())
-					&& let Some(name) = path.file_name().and_then(|n| n.to_str())
-					&& name.to_lowercase().starts_with("ff")
-					&& name.to_lowercase().ends_with(".exe")
-				{
-					let dest_path = target_dir.join(name);
-					let mut outfile =
-						File::create(&dest_path).map_err(|e| format!("Failed to create {}: {}", name, e))?;
-					io::copy(&mut file, &mut outfile).map_err(|e| format!("Extraction failed for {}: {}", name, e))?;
-					found_any = true;
-				}
-			}
-			let _ = fs::remove_file(zip_path);
-			if !found_any {
+			let extracted = extract_tools(&archive_path, target_dir);
+			let _ = fs::remove_file(archive_path);
+			let extracted = extracted?;
+			if !extracted.contains(&platform::exe_name("ffmpeg")) {
 				return Err("No ffmpeg tools found in downloaded archive".into());
 			}
+			installed.extend(extracted);
 		}
+		installed
 	};
 
 	if result.is_ok() {
@@ -126,3 +376,397 @@ pub fn download_tools_script(
 	}
 	result
 }
+
+#[cfg(test)]
+mod tests {
+	use std::{collections::HashMap, io::BufRead, net::TcpListener, sync::mpsc, thread};
+
+	use super::*;
+
+	struct Route {
+		body: Vec<u8>,
+		ranges: bool,
+	}
+
+	fn route(body: &[u8]) -> Route { Route { body: body.to_vec(), ranges: true } }
+
+	fn serve(routes: Vec<(&str, Route)>) -> (String, Arc<Mutex<Vec<String>>>) {
+		let listener = TcpListener::bind("127.0.0.1:0").expect("Bind test server");
+		let base = format!("http://{}", listener.local_addr().expect("Test server address"));
+		let routes: HashMap<String, Route> = routes.into_iter().map(|(p, r)| (p.to_string(), r)).collect();
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let log = requests.clone();
+		thread::spawn(move || {
+			for mut stream in listener.incoming().map_while(Result::ok) {
+				let mut reader = BufReader::new(stream.try_clone().expect("Clone test stream"));
+				let mut request_line = String::new();
+				let _ = reader.read_line(&mut request_line);
+				let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+				let mut range = None;
+				for line in reader.by_ref().lines().map_while(Result::ok).take_while(|l| !l.is_empty()) {
+					if let Some((name, value)) = line.split_once(':')
+						&& name.eq_ignore_ascii_case("range")
+					{
+						range = value
+							.trim()
+							.strip_prefix("bytes=")
+							.and_then(|r| r.trim_end_matches('-').parse::<usize>().ok());
+					}
+				}
+				log.lock().unwrap().push(match range {
+					Some(start) => format!("{} bytes={}-", path, start),
+					None => path.clone(),
+				});
+				let (status, headers, body): (&str, String, &[u8]) = match routes.get(&path) {
+					None => ("404 Not Found", String::new(), b""),
+					Some(r) => match range.filter(|_| r.ranges) {
+						Some(start) if start >= r.body.len() => {
+							("416 Range Not Satisfiable", format!("Content-Range: bytes */{}\r\n", r.body.len()), b"")
+						}
+						Some(start) => (
+							"206 Partial Content",
+							format!("Content-Range: bytes {}-{}/{}\r\n", start, r.body.len() - 1, r.body.len()),
+							&r.body[start..],
+						),
+						None => ("200 OK", String::new(), &r.body[..]),
+					},
+				};
+				let _ = write!(
+					stream,
+					"HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+					status,
+					headers,
+					body.len()
+				);
+				let _ = stream.write_all(body);
+			}
+		});
+		(base, requests)
+	}
+
+	fn test_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("tubex-{}-{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).expect("Create test directory");
+		dir
+	}
+
+	fn sha256(data: &[u8]) -> String { format!("{:x}", Sha256::digest(data)) }
+
+	fn source(base: &str, mirror: &str, asset: &str) -> ToolSource {
+		ToolSource {
+			url: format!("{}/{}/{}", base, mirror, asset),
+			checksums_url: format!("{}/{}/SHA2-256SUMS", base, mirror),
+		}
+	}
+
+	fn tar_xz(entries: &[(&str, &[u8])]) -> Vec<u8> {
+		let mut builder = tar::Builder::new(xz2::write::XzEncoder::new(Vec::new(), 6));
+		for (path, data) in entries {
+			let mut header = tar::Header::new_gnu();
+			header.set_size(data.len() as u64);
+			header.set_mode(0o644);
+			header.set_cksum();
+			builder.append_data(&mut header, path, *data).expect("Append tar entry");
+		}
+		builder.into_inner().and_then(|xz| xz.finish()).expect("Finish tar.xz")
+	}
+
+	fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+		let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+		for (path, data) in entries {
+			writer.start_file(*path, zip::write::SimpleFileOptions::default()).expect("Start zip entry");
+			writer.write_all(data).expect("Write zip entry");
+		}
+		writer.finish().expect("Finish zip").into_inner()
+	}
+
+	#[test]
+	fn downloads_and_installs_tools_from_sources() {
+		let yt_dlp = b"#!/bin/sh\necho 2024.01.01\n".as_slice();
+		let (ffmpeg, ffprobe) = (b"ffmpeg binary".as_slice(), b"ffprobe binary".as_slice());
+		let archive = tar_xz(&[
+			(&format!("ffmpeg-master/bin/{}", platform::exe_name("ffmpeg")), ffmpeg),
+			(&format!("ffmpeg-master/bin/{}", platform::exe_name("ffprobe")), ffprobe),
+			("ffmpeg-master/doc/README.txt", b"docs"),
+		]);
+		let sums = format!("{}  yt-dlp_linux\n{}  ffmpeg.tar.xz\n", sha256(yt_dlp), sha256(&archive));
+		let (base, _) = serve(vec![
+			("/tools/yt-dlp_linux", route(yt_dlp)),
+			("/tools/ffmpeg.tar.xz", route(&archive)),
+			("/tools/SHA2-256SUMS", route(sums.as_bytes())),
+		]);
+		let sources = ToolSources {
+			yt_dlp: vec![source(&base, "tools", "yt-dlp_linux")],
+			ffmpeg: vec![source(&base, "tools", "ffmpeg.tar.xz")],
+		};
+		let dir = test_dir("install");
+		let (tx, _rx) = mpsc::channel();
+
+		let installed = download_tools_script(&dir, &sources, tx, true, true).unwrap();
+
+		assert_eq!(
+			installed,
+			[platform::exe_name("yt-dlp"), platform::exe_name("ffmpeg"), platform::exe_name("ffprobe")]
+		);
+		assert_eq!(fs::read(dir.join(platform::exe_name("yt-dlp"))).unwrap(), yt_dlp);
+		assert_eq!(fs::read(dir.join(platform::exe_name("ffmpeg"))).unwrap(), ffmpeg);
+		assert_eq!(fs::read(dir.join(platform::exe_name("ffprobe"))).unwrap(), ffprobe);
+		assert!(!dir.join("README.txt").exists());
+		assert!(!dir.join("ffmpeg.tar.xz").exists());
+	}
+
+	#[test]
+	fn mirrors_are_verified_against_upstream_checksums() {
+		let sources = ToolSources::for_current_platform(&["https://mirror.example/tools/".to_string()]);
+		for list in [&sources.yt_dlp, &sources.ffmpeg] {
+			assert_eq!(list.len(), 2);
+			assert!(list[1].url.starts_with("https://mirror.example/tools/"));
+			assert_eq!(list[1].checksums_url, list[0].checksums_url);
+		}
+	}
+
+	#[test]
+	fn tampered_mirror_with_matching_sums_is_rejected() {
+		let genuine = b"genuine yt-dlp".as_slice();
+		let tampered = b"tampered yt-dlp".as_slice();
+		let (base, requests) = serve(vec![
+			("/upstream/SHA2-256SUMS", route(format!("{}  yt-dlp_linux\n", sha256(genuine)).as_bytes())),
+			("/mirror/yt-dlp_linux", route(tampered)),
+			("/mirror/SHA2-256SUMS", route(format!("{}  yt-dlp_linux\n", sha256(tampered)).as_bytes())),
+		]);
+		let sources = ToolSources {
+			yt_dlp: vec![ToolSource {
+				url: format!("{}/mirror/yt-dlp_linux", base),
+				checksums_url: format!("{}/upstream/SHA2-256SUMS", base),
+			}],
+			ffmpeg: Vec::new(),
+		};
+		let dir = test_dir("tampered-mirror");
+		let (tx, _rx) = mpsc::channel();
+
+		let err = download_tools_script(&dir, &sources, tx, true, false).unwrap_err();
+
+		assert!(err.starts_with("Checksum mismatch for yt-dlp_linux"), "{}", err);
+		assert!(!requests.lock().unwrap().contains(&"/mirror/SHA2-256SUMS".to_string()));
+		assert!(!dir.join(platform::exe_name("yt-dlp")).exists());
+	}
+
+	#[test]
+	fn unreachable_source_falls_over_to_next_mirror() {
+		let yt_dlp = b"yt-dlp".as_slice();
+		let (base, requests) = serve(vec![
+			("/mirror/yt-dlp_linux", route(yt_dlp)),
+			("/mirror/SHA2-256SUMS", route(format!("{}  yt-dlp_linux\n", sha256(yt_dlp)).as_bytes())),
+		]);
+		let sources = ToolSources {
+			yt_dlp: vec![source(&base, "missing", "yt-dlp_linux"), source(&base, "mirror", "yt-dlp_linux")],
+			ffmpeg: Vec::new(),
+		};
+		let dir = test_dir("failover");
+		let (tx, _rx) = mpsc::channel();
+
+		assert!(download_tools_script(&dir, &sources, tx, true, false).is_ok());
+		assert_eq!(fs::read(dir.join(platform::exe_name("yt-dlp"))).unwrap(), yt_dlp);
+		assert_eq!(requests.lock().unwrap()[0], "/missing/SHA2-256SUMS");
+	}
+
+	#[test]
+	fn checksum_mismatch_discards_file_and_tries_next_mirror() {
+		let yt_dlp = b"genuine yt-dlp".as_slice();
+		let sums = format!("{}  yt-dlp_linux\n", sha256(yt_dlp));
+		let (base, requests) = serve(vec![
+			("/tampered/yt-dlp_linux", route(b"tampered yt-dlp")),
+			("/tampered/SHA2-256SUMS", route(sums.as_bytes())),
+			("/mirror/yt-dlp_linux", route(yt_dlp)),
+			("/mirror/SHA2-256SUMS", route(sums.as_bytes())),
+		]);
+		let sources = ToolSources {
+			yt_dlp: vec![source(&base, "tampered", "yt-dlp_linux"), source(&base, "mirror", "yt-dlp_linux")],
+			ffmpeg: Vec::new(),
+		};
+		let dir = test_dir("checksum");
+		let (tx, _rx) = mpsc::channel();
+
+		assert!(download_tools_script(&dir, &sources, tx, true, false).is_ok());
+
+		assert_eq!(fs::read(dir.join(platform::exe_name("yt-dlp"))).unwrap(), yt_dlp);
+		assert!(requests.lock().unwrap().contains(&"/tampered/yt-dlp_linux".to_string()));
+		let leftovers: Vec<_> =
+			fs::read_dir(&dir).unwrap().flatten().map(|e| e.file_name().to_string_lossy().into_owned()).collect();
+		assert_eq!(leftovers, [platform::exe_name("yt-dlp")]);
+	}
+
+	#[test]
+	fn checksum_mismatch_on_every_mirror_fails() {
+		let (base, _) = serve(vec![
+			("/tampered/yt-dlp_linux", route(b"tampered yt-dlp")),
+			("/tampered/SHA2-256SUMS", route(format!("{}  yt-dlp_linux\n", sha256(b"genuine")).as_bytes())),
+		]);
+		let sources = ToolSources { yt_dlp: vec![source(&base, "tampered", "yt-dlp_linux")], ffmpeg: Vec::new() };
+		let dir = test_dir("checksum-fail");
+		let (tx, _rx) = mpsc::channel();
+
+		let err = download_tools_script(&dir, &sources, tx, true, false).unwrap_err();
+
+		assert!(err.starts_with("Checksum mismatch for yt-dlp_linux"), "{}", err);
+		assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+	}
+
+	#[test]
+	fn resumes_partial_download_with_range() {
+		let body: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+		let (base, requests) = serve(vec![("/file", route(&body))]);
+		let dir = test_dir("resume");
+		let part = dir.join("file.part");
+		fs::write(&part, &body[..8_000]).unwrap();
+
+		fetch_partial(&format!("{}/file", base), &part, |_, _| {}).unwrap();
+
+		assert_eq!(fs::read(&part).unwrap(), body);
+		assert_eq!(*requests.lock().unwrap(), ["/file bytes=8000-"]);
+	}
+
+	#[test]
+	fn restarts_when_server_ignores_range() {
+		let body = b"complete file from a server without range support".to_vec();
+		let (base, requests) = serve(vec![("/file", Route { body: body.clone(), ranges: false })]);
+		let dir = test_dir("no-range");
+		let part = dir.join("file.part");
+		fs::write(&part, b"stale bytes").unwrap();
+
+		fetch_partial(&format!("{}/file", base), &part, |_, _| {}).unwrap();
+
+		assert_eq!(fs::read(&part).unwrap(), body);
+		assert_eq!(*requests.lock().unwrap(), ["/file bytes=11-"]);
+	}
+
+	#[test]
+	fn unsatisfiable_range_keeps_complete_file() {
+		let body = b"already fully downloaded".to_vec();
+		let (base, requests) = serve(vec![("/file", route(&body))]);
+		let dir = test_dir("range-complete");
+		let part = dir.join("file.part");
+		fs::write(&part, &body).unwrap();
+
+		fetch_partial(&format!("{}/file", base), &part, |_, _| {}).unwrap();
+
+		assert_eq!(fs::read(&part).unwrap(), body);
+		assert_eq!(requests.lock().unwrap().len(), 1);
+	}
+
+	#[test]
+	fn unsatisfiable_range_restarts_oversized_file() {
+		let body = b"the current release".to_vec();
+		let (base, requests) = serve(vec![("/file", route(&body))]);
+		let dir = test_dir("range-oversized");
+		let part = dir.join("file.part");
+		let stale = b"a previous, much longer release of the file";
+		fs::write(&part, stale).unwrap();
+
+		fetch_partial(&format!("{}/file", base), &part, |_, _| {}).unwrap();
+
+		assert_eq!(fs::read(&part).unwrap(), body);
+		assert_eq!(*requests.lock().unwrap(), [format!("/file bytes={}-", stale.len()), "/file".to_string()]);
+	}
+
+	#[test]
+	fn partial_files_are_not_shared_between_mirrors() {
+		let yt_dlp = b"yt-dlp from the mirror".as_slice();
+		let (base, requests) = serve(vec![
+			("/mirror/yt-dlp_linux", route(yt_dlp)),
+			("/mirror/SHA2-256SUMS", route(format!("{}  yt-dlp_linux\n", sha256(yt_dlp)).as_bytes())),
+		]);
+		let sources = ToolSources {
+			yt_dlp: vec![source(&base, "offline", "yt-dlp_linux"), source(&base, "mirror", "yt-dlp_linux")],
+			ffmpeg: Vec::new(),
+		};
+		let dir = test_dir("part-per-mirror");
+		let filename = platform::exe_name("yt-dlp");
+		fs::write(partial_path(&dir, &filename, &sources.yt_dlp[0]), b"half of another build").unwrap();
+		let (tx, _rx) = mpsc::channel();
+
+		assert!(download_tools_script(&dir, &sources, tx, true, false).is_ok());
+
+		assert_eq!(fs::read(dir.join(&filename)).unwrap(), yt_dlp);
+		assert!(requests.lock().unwrap().contains(&"/mirror/yt-dlp_linux".to_string()));
+		assert!(!partial_path(&dir, &filename, &sources.yt_dlp[0]).exists());
+	}
+
+	#[test]
+	fn extracts_tools_from_tar_xz() {
+		let dir = test_dir("tar-xz");
+		let archive_path = dir.join("bundle.tar.xz");
+		fs::write(
+			&archive_path,
+			tar_xz(&[
+				(&format!("bundle/{}", platform::exe_name("yt-dlp_macos")), b"yt-dlp"),
+				(&format!("bundle/bin/{}", platform::exe_name("ffmpeg")), b"ffmpeg"),
+				("bundle/LICENSE", b"license"),
+			]),
+		)
+		.unwrap();
+
+		let installed = extract_tools(&archive_path, &dir).unwrap();
+
+		assert_eq!(installed, [platform::exe_name("yt-dlp"), platform::exe_name("ffmpeg")]);
+		assert_eq!(fs::read(dir.join(platform::exe_name("yt-dlp"))).unwrap(), b"yt-dlp");
+		assert!(!dir.join("LICENSE").exists());
+	}
+
+	#[test]
+	fn extracts_tools_from_zip() {
+		let dir = test_dir("zip");
+		let archive_path = dir.join("ffmpeg-release-essentials.ZIP");
+		fs::write(
+			&archive_path,
+			zip(&[
+				(&format!("ffmpeg-7.1-essentials_build/bin/{}", platform::exe_name("ffmpeg")), b"ffmpeg"),
+				(&format!("ffmpeg-7.1-essentials_build/bin/{}", platform::exe_name("ffplay")), b"ffplay"),
+				("ffmpeg-7.1-essentials_build/README.txt", b"readme"),
+			]),
+		)
+		.unwrap();
+
+		let installed = extract_tools(&archive_path, &dir).unwrap();
+
+		assert_eq!(installed, [platform::exe_name("ffmpeg"), platform::exe_name("ffplay")]);
+		assert_eq!(fs::read(dir.join(platform::exe_name("ffplay"))).unwrap(), b"ffplay");
+		assert!(!dir.join("README.txt").exists());
+	}
+
+	#[test]
+	fn rejects_unsupported_archives() {
+		let dir = test_dir("unsupported");
+		let archive_path = dir.join("tools.rar");
+		fs::write(&archive_path, b"rar").unwrap();
+		assert!(extract_tools(&archive_path, &dir).is_err());
+		let (tx, _rx) = mpsc::channel();
+		let empty_path = dir.join("empty.zip");
+		fs::write(&empty_path, zip(&[("notes.txt", b"nothing here")])).unwrap();
+		assert!(install_from_archive(&empty_path, &dir, tx).is_err());
+	}
+
+	#[test]
+	fn recognises_bundled_tool_names() {
+		assert_eq!(bundled_tool_name(&platform::exe_name("FFmpeg")), Some(platform::exe_name("ffmpeg")));
+		assert_eq!(bundled_tool_name(&platform::exe_name("yt-dlp_linux_aarch64")), Some(platform::exe_name("yt-dlp")));
+		assert_eq!(bundled_tool_name("ffmpeg.1"), None);
+		assert_eq!(bundled_tool_name("yt-dlp.tar.gz"), None);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn installed_tools_are_executable() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let dir = test_dir("exec");
+		let path = dir.join("tool");
+		fs::write(&path, b"#!/bin/sh\n").unwrap();
+		fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+		platform::set_executable(&path).unwrap();
+
+		assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o111, 0o111);
+	}
+}
@@ -0,0 +1,174 @@
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+};
+
+use crate::{config::ConfigManager, platform, startup};
+
+#[derive(Clone)]
+pub struct VersionManager {
+	log: Arc<Mutex<String>>,
+}
+
+impl VersionManager {
+	pub fn new() -> Self { Self { log: Arc::new(Mutex::new(String::new())) } }
+
+	pub fn get_log(&self) -> String { self.log.lock().expect("Update log lock failed").clone() }
+
+	fn append_log(&self, line: &str) {
+		let mut log = self.log.lock().expect("Update log lock failed");
+		log.push_str(line.trim_end());
+		log.push('\n');
+	}
+
+	pub fn installed_version(path: &str) -> Option<String> {
+		platform::command(path)
+			.arg("--version")
+			.output()
+			.ok()
+			.filter(|o| o.status.success())
+			.map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+	}
+
+	pub fn latest_version(channel: &str) -> Result<String, String> {
+		let repo = match channel {
+			"nightly" => "yt-dlp/yt-dlp-nightly-builds",
+			"master" => "yt-dlp/yt-dlp-master-builds",
+			_ => "yt-dlp/yt-dlp",
+		};
+		let body = ureq::get(&format!("https://api.github.com/repos/{}/releases/latest", repo))
+			.header("Accept", "application/vnd.github+json")
+			.call()
+			.and_then(|mut r| r.body_mut().read_to_string())
+			.map_err(|e| format!("Release check failed: {}", e))?;
+		serde_json::from_str::<serde_json::Value>(&body)
+			.ok()
+			.and_then(|v| v["tag_name"].as_str().map(String::from))
+			.ok_or_else(|| "Release check returned no tag".to_string())
+	}
+
+	pub fn is_app_managed(path: &str) -> bool {
+		let dir = startup::tools_dir();
+		platform::find_executable(path).is_some_and(|exe| exe.parent() == Some(dir.as_path()))
+	}
+
+	pub fn has_rollback(path: &str) -> bool {
+		platform::find_executable(path).is_some_and(|p| backup_path(&p).is_file())
+	}
+
+	pub fn update(&self, cfg: &Arc<Mutex<ConfigManager>>) -> Result<String, String> {
+		let result = self.run_update(cfg);
+		if let Err(e) = &result {
+			self.append_log(e);
+		}
+		result
+	}
+
+	fn run_update(&self, cfg: &Arc<Mutex<ConfigManager>>) -> Result<String, String> {
+		let (path, channel, pinned) = {
+			let c = cfg.lock().expect("Config manager lock failed");
+			(c.get_yt_dlp_path(), c.get_update_channel(), c.get_pinned_version())
+		};
+		let exe = platform::find_executable(&path).ok_or_else(|| format!("yt-dlp not found at {}", path))?;
+		let exe_str = exe.to_string_lossy().to_string();
+		let before = Self::installed_version(&exe_str);
+		if let Some(v) = &pinned
+			&& before.as_ref() == Some(v)
+		{
+			self.append_log(&format!("yt-dlp is pinned to {}, skipping update.", v));
+			return Ok(v.clone());
+		}
+
+		let target = match &pinned {
+			Some(v) => format!("{}@{}", channel, v),
+			None => {
+				let latest = Self::latest_version(&channel)?;
+				if before.as_ref() == Some(&latest) {
+					self.append_log(&format!("yt-dlp is already at the latest {} version {}.", channel, latest));
+					return Ok(latest);
+				}
+				channel
+			}
+		};
+		self.append_log(&format!(
+			"Updating {} ({}) to {}...",
+			exe.display(),
+			before.as_deref().unwrap_or("unknown version"),
+			target
+		));
+
+		let staged = exe.with_file_name(format!("{}.staged", backup_name(&exe)));
+		fs::copy(&exe, &staged).map_err(|e| format!("Failed to back up yt-dlp: {}", e))?;
+		let output = platform::command(&exe).arg("--update-to").arg(&target).output();
+		let output = match output {
+			Ok(o) => o,
+			Err(e) => {
+				let _ = fs::remove_file(&staged);
+				return Err(format!("Failed to run yt-dlp: {}", e));
+			}
+		};
+		for line in
+			String::from_utf8_lossy(&output.stdout).lines().chain(String::from_utf8_lossy(&output.stderr).lines())
+		{
+			self.append_log(line);
+		}
+
+		let after = Self::installed_version(&exe_str);
+		if after.is_some() && after != before {
+			fs::rename(&staged, backup_path(&exe)).map_err(|e| format!("Failed to keep previous yt-dlp: {}", e))?;
+		} else {
+			let _ = fs::remove_file(&staged);
+		}
+
+		match (output.status.success(), after) {
+			(true, Some(v)) => {
+				self.append_log(&format!("yt-dlp is now at {}.", v));
+				Ok(v)
+			}
+			_ => Err(format!("yt-dlp update exited with code {:?}", output.status.code())),
+		}
+	}
+
+	pub fn rollback(&self, cfg: &Arc<Mutex<ConfigManager>>) -> Result<String, String> {
+		let result = self.run_rollback(cfg);
+		if let Err(e) = &result {
+			self.append_log(e);
+		}
+		result
+	}
+
+	fn run_rollback(&self, cfg: &Arc<Mutex<ConfigManager>>) -> Result<String, String> {
+		let path = cfg.lock().expect("Config manager lock failed").get_yt_dlp_path();
+		let exe = platform::find_executable(&path).ok_or_else(|| format!("yt-dlp not found at {}", path))?;
+		let backup = backup_path(&exe);
+		if !backup.is_file() {
+			return Err("No previous yt-dlp version to roll back to.".into());
+		}
+		let swap = exe.with_file_name(format!("{}.swap", backup_name(&exe)));
+		fs::rename(&exe, &swap).map_err(|e| format!("Failed to move current yt-dlp aside: {}", e))?;
+		if let Err(e) = fs::rename(&backup, &exe) {
+			let _ = fs::rename(&swap, &exe);
+			return Err(format!("Failed to restore previous yt-dlp: {}", e));
+		}
+		let _ = fs::rename(&swap, &backup);
+		let Some(version) = Self::installed_version(&exe.to_string_lossy()) else {
+			self.append_log("Rolled back yt-dlp to an unknown version.");
+			return Ok("unknown version".into());
+		};
+		{
+			let mut c = cfg.lock().expect("Config manager lock failed");
+			c.set_pinned_version(&version);
+			c.flush();
+		}
+		self.append_log(&format!(
+			"Rolled back yt-dlp to {} and pinned it. Clear the pin to allow updates again.",
+			version
+		));
+		Ok(version)
+	}
+}
+
+fn backup_name(exe: &Path) -> String { exe.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default() }
+
+fn backup_path(exe: &Path) -> PathBuf { exe.with_file_name(format!("{}.previous", backup_name(exe))) }
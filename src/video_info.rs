@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Format {
@@ -13,6 +15,19 @@ pub struct Format {
 	pub filesize: Option<u64>,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct SubtitleTrack {
+	pub ext: Option<String>,
+	pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Chapter {
+	pub title: Option<String>,
+	pub start_time: f64,
+	pub end_time: f64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct VideoInfo {
 	pub id: String,
@@ -26,8 +41,29 @@ pub struct VideoInfo {
 	pub playlist_count: Option<u32>,
 	#[serde(rename = "_type")]
 	pub result_type: Option<String>,
+	pub ie_key: Option<String>,
+	pub extractor_key: Option<String>,
+	pub channel: Option<String>,
+	pub upload_date: Option<String>,
+	pub ext: Option<String>,
+	pub playlist_title: Option<String>,
+	pub playlist_index: Option<u32>,
 	#[serde(default)]
 	pub formats: Vec<Format>,
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub subtitles: BTreeMap<String, Vec<SubtitleTrack>>,
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub automatic_captions: BTreeMap<String, Vec<SubtitleTrack>>,
+	#[serde(default, deserialize_with = "null_as_default")]
+	pub chapters: Vec<Chapter>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+	D: Deserializer<'de>,
+	T: Default + Deserialize<'de>,
+{
+	Ok(Option::deserialize(deserializer)?.unwrap_or_default())
 }
 
 impl VideoInfo {